@@ -3,42 +3,328 @@ use rand::prelude::*;
 use bevy::math::*;
 
 use crate::physics::*;
+use crate::player::*;
 use crate::includes::*;
 
+use crate::effects::*;
 use crate::health::*;
+use crate::spatial::*;
+use crate::steering::*;
 use crate::team::*;
+use crate::topology::*;
+use crate::zone::*;
+use crate::scoring::*;
+
+//anything smaller than this crumbles to debris instead of splitting again
+const ASTEROID_MIN_FRAGMENT_RADIUS: f32 = 12.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AsteroidArchetype {
+    Rock,
+    SmallRock,
+    //big, slow and full of crystals
+    CrystalPlanetoid,
+    //tough and worthless, good cover
+    IronRock,
+    //blows up and damages everything nearby when destroyed
+    ExplosiveRock,
+}
+
+pub struct Explosion {
+    pub radius: f32,
+    pub damage: i32,
+}
+
+pub struct AsteroidStats {
+    pub radius: f32,
+    pub mass: f32,
+    pub health: i32,
+    pub crystal_yield: u32,
+    pub crystal_chance: f32,
+    pub score: u32,
+    pub sides: Option<u32>,
+    pub color: Color,
+    pub explosion: Option<Explosion>,
+}
+
+impl AsteroidArchetype {
+    //the archetype table, values are for a full sized rock and scale down for fragments
+    pub fn stats(self) -> AsteroidStats {
+        match self {
+            AsteroidArchetype::Rock => AsteroidStats {
+                radius: 30.0,
+                mass: 50.0,
+                health: 6,
+                crystal_yield: 4,
+                crystal_chance: 0.6,
+                score: 100,
+                sides: None,
+                color: Color::srgb(0.55, 0.5, 0.4),
+                explosion: None,
+            },
+            AsteroidArchetype::SmallRock => AsteroidStats {
+                radius: 16.0,
+                mass: 15.0,
+                health: 2,
+                crystal_yield: 1,
+                crystal_chance: 0.5,
+                score: 50,
+                sides: None,
+                color: Color::srgb(0.6, 0.55, 0.45),
+                explosion: None,
+            },
+            AsteroidArchetype::CrystalPlanetoid => AsteroidStats {
+                radius: 55.0,
+                mass: 200.0,
+                health: 16,
+                crystal_yield: 12,
+                crystal_chance: 0.9,
+                score: 300,
+                sides: None,
+                color: Color::srgb(0.6, 0.55, 0.2),
+                explosion: None,
+            },
+            AsteroidArchetype::IronRock => AsteroidStats {
+                radius: 28.0,
+                mass: 120.0,
+                health: 14,
+                crystal_yield: 0,
+                crystal_chance: 0.0,
+                score: 150,
+                sides: Some(6),
+                color: Color::srgb(0.45, 0.47, 0.55),
+                explosion: None,
+            },
+            AsteroidArchetype::ExplosiveRock => AsteroidStats {
+                radius: 24.0,
+                mass: 40.0,
+                health: 3,
+                crystal_yield: 1,
+                crystal_chance: 0.3,
+                score: 200,
+                sides: Some(8),
+                color: Color::srgb(0.75, 0.3, 0.2),
+                explosion: Some(Explosion {
+                    radius: 150.0,
+                    damage: 4,
+                }),
+            },
+        }
+    }
+}
 
 #[derive(Component)]
-pub struct Asteroid;
+pub struct Asteroid {
+    pub archetype: AsteroidArchetype,
+    //crystals left inside, a rock at zero is mined out
+    pub crystal_yield: u32,
+    //odds that a hit knocks one of them loose
+    pub crystal_chance: f32,
+}
+
+impl Asteroid {
+    pub fn is_depleted(&self) -> bool {
+        self.crystal_yield == 0
+    }
+}
+
+//mined out rocks go dark so a worked field reads differently from a fresh one
+pub fn asteroid_color(asteroid: &Asteroid) -> Color {
+    let color = asteroid.archetype.stats().color;
+
+    if asteroid.is_depleted() && asteroid.archetype != AsteroidArchetype::IronRock {
+        color.darker(0.25)
+    } else {
+        color
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_asteroid(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    archetype: AsteroidArchetype,
+    position: Vec2,
+    velocity: Vec2,
+    radius: f32,
+    crystal_yield: u32,
+) {
+    let stats = archetype.stats();
+
+    //mass and toughness scale with the size of the rock
+    let scale = radius / stats.radius;
+    let health = ((stats.health as f32 * scale).round() as i32).max(1);
+    let asteroid = Asteroid {
+        archetype,
+        crystal_yield,
+        crystal_chance: stats.crystal_chance,
+    };
+    let color = asteroid_color(&asteroid);
+    //small rocks tumble faster than big ones
+    let max_spin = 40.0 / radius;
+    let spin = rand::rng().random_range(-max_spin..max_spin);
+    let mesh = match stats.sides {
+        Some(sides) => meshes.add(RegularPolygon::new(radius, sides)),
+        None => meshes.add(Circle::new(radius)),
+    };
 
-pub fn spawn_asteroids(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, mut materials: ResMut<Assets<ColorMaterial>>)
+    commands.spawn((
+        asteroid,
+        Velocity(velocity),
+        AngularVelocity(spin),
+        WrapsAroundCamera,
+        Transform {
+            translation: position.extend(9.0),
+            ..default()
+        },
+        Collider { radius },
+        Mass(stats.mass * scale * scale),
+        Mesh2d(mesh),
+        MeshMaterial2d(materials.add(ColorMaterial::from(color))),
+        Health(health),
+        MaxHealth(health),
+        ScoreValue((stats.score as f32 * scale) as u32),
+        Team::None,
+        CollisionLayers::new(LAYER_ASTEROID, LAYER_ALL),
+        //ai steers around rocks instead of bouncing off them
+        Obstacle
+    ));
+}
+
+pub fn spawn_asteroids(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    zones: Res<Zones>,
+    current_zone: Res<CurrentZone>,
+    players: Query<&Transform, With<Player>>,
+)
 {
     let mut rng = rand::rng();
+    let zone = zones.get(current_zone.0);
+    let player_positions: Vec<Vec2> = players.iter().map(|transform| transform.translation.truncate()).collect();
 
-    for _i in 0..100 {
+    for _i in 0..zone.asteroid_count {
         let half_w = WORLD_WIDTH / 2.0;
         let half_h = WORLD_HEIGHT / 2.0;
-        
-        let p_x: f32 = rng.random_range(-half_w..half_w);
-        let p_y: f32 = rng.random_range(-half_h..half_h);
-        
+
+        let position = spawn_clear_of(&player_positions, || {
+            Vec2::new(rng.random_range(-half_w..half_w), rng.random_range(-half_h..half_h))
+        });
+
         let v_x: f32 = rng.random_range(-20.0..20.0);
         let v_y: f32 = rng.random_range(-20.0..20.0);
-        
-        commands.spawn((
-            Asteroid,
-            Velocity(Vec2::new(v_x, v_y)),
-            WrapsAroundCamera,
-            Transform {
-                translation: Vec3::new(p_x, p_y, 9.0),
-                ..default()
-            },
-            Collider { radius: 30.0 },
-            Mass(50.0),
-            Mesh2d(meshes.add(Circle::new(30.0))),
-            MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgb(0.5, 0.5, 0.5)))),
-            Health(3),
-            Team::None
-        ));
+
+        let archetype = zone.pick_asteroid(&mut rng);
+        let stats = archetype.stats();
+
+        spawn_asteroid(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            archetype,
+            position,
+            Vec2::new(v_x, v_y),
+            stats.radius,
+            stats.crystal_yield,
+        );
+    }
+}
+
+//large rocks break into smaller ones that carry on with the parent's momentum
+//and share whatever crystals it still had
+pub fn fragment_asteroids(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut messages: MessageReader<EntityDestroyedMessage>,
+    asteroids: Query<(&Asteroid, &Velocity, &Collider)>,
+) {
+    let mut rng = rand::rng();
+
+    for message in messages.read() {
+        let Ok((asteroid, velocity, collider)) = asteroids.get(message.entity) else {
+            continue;
+        };
+
+        //explosive rocks go off instead of breaking up
+        if asteroid.archetype.stats().explosion.is_some() {
+            continue;
+        }
+
+        let child_radius = collider.radius * 0.6;
+        if child_radius < ASTEROID_MIN_FRAGMENT_RADIUS {
+            continue;
+        }
+
+        let count: u32 = rng.random_range(2..=3);
+        let base_angle = rng.random_range(0.0..std::f32::consts::TAU);
+
+        for i in 0..count {
+            let direction = Vec2::from_angle(base_angle + i as f32 * std::f32::consts::TAU / count as f32);
+            //hand out the remaining crystals as evenly as possible
+            let crystal_yield = asteroid.crystal_yield / count + u32::from(i < asteroid.crystal_yield % count);
+
+            spawn_asteroid(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                asteroid.archetype,
+                message.position + direction * child_radius,
+                velocity.0 + direction * rng.random_range(20.0..60.0),
+                child_radius,
+                crystal_yield,
+            );
+        }
+    }
+}
+
+//explosive rocks hurt everything in their blast radius, credited to whoever set them off
+pub fn detonate_asteroids(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    grid: Res<SpatialGrid>,
+    mut messages: MessageReader<EntityDestroyedMessage>,
+    asteroids: Query<&Asteroid>,
+    mut writer: MessageWriter<DamageMessage>,
+) {
+    for message in messages.read() {
+        let Ok(asteroid) = asteroids.get(message.entity) else {
+            continue;
+        };
+
+        let Some(explosion) = asteroid.archetype.stats().explosion else {
+            continue;
+        };
+
+        for candidate in grid.query(message.position, explosion.radius) {
+            if candidate.entity == message.entity {
+                continue;
+            }
+
+            //only centres inside the blast count, grazing the edge isn't enough
+            if wrapped_distance(candidate.position, message.position) > explosion.radius {
+                continue;
+            }
+
+            writer.write(DamageMessage {
+                source: message.killer,
+                target: candidate.entity,
+                amount: explosion.damage,
+                kind: DamageKind::Explosion,
+            });
+        }
+
+        spawn_spark(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            message.position,
+            explosion.radius * 0.25,
+            0.5,
+            Color::srgb(1.0, 0.4, 0.1),
+        );
     }
-}
\ No newline at end of file
+}
@@ -4,7 +4,11 @@ use crate::physics::*;
 use crate::player::*;
 use crate::includes::*;
 use crate::worker::*;
+use crate::asteroid::*;
+use crate::shooting::*;
 use crate::navigation::*;
+use crate::sensor::*;
+use crate::topology::*;
 use std::collections::HashSet;
 
 #[derive(Component)]
@@ -27,54 +31,132 @@ pub fn spawn_crystal(
         Velocity(impact_dir + Vec2::new(random_drift_x, random_drift_y)),
         Collider { radius: 8.0 },
         Mass(1.0),
+        //collected by touch, never bounced around by what touches it
+        CollisionLayers::new(LAYER_PICKUP, LAYER_PLAYER | LAYER_ENEMY | LAYER_PROJECTILE),
+        NonSolid,
+        OverlapSensor::new(8.0, LAYER_PLAYER | LAYER_ENEMY),
         Transform::from_translation(position),
         Mesh2d(meshes.add(RegularPolygon::new(8.0, 3))),
         MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgb(1.0, 0.9, 0.0)))),
     ));
 }
 
+//shooting an asteroid may knock a crystal loose in the direction of the shot
+pub fn spawn_crystals_from_hits(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut messages: MessageReader<ProjectileHitMessage>,
+    mut asteroids: Query<(Entity, &Transform, &mut Asteroid)>,
+) {
+    let mut rng = rand::rng();
+
+    for hit in messages.read() {
+        let Ok((asteroid_entity, asteroid_transform, mut asteroid)) = asteroids.get_mut(hit.target) else {
+            continue;
+        };
+
+        if asteroid.is_depleted() || !rng.random_bool(asteroid.crystal_chance as f64) {
+            continue;
+        }
+
+        asteroid.crystal_yield -= 1;
+
+        if asteroid.is_depleted() {
+            commands
+                .entity(asteroid_entity)
+                .insert(MeshMaterial2d(materials.add(ColorMaterial::from(asteroid_color(&asteroid)))));
+        }
+
+        spawn_crystal(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            asteroid_transform.translation,
+            hit.direction * 50.0,
+        );
+    }
+}
+
+//who gets a crystal that's in reach of more than one collector
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum CrystalClaim {
+    Player,
+    Worker(Entity),
+}
+
+//the closest collector touching the crystal takes it, so a worker can snatch one out from under the player.
+//claims are settled closest first with ties broken by entity, so the outcome never depends on message order
 pub fn crystal_impacts(
     mut commands: Commands,
     mut sinibombs: ResMut<Sinibombs>,
     mut score: ResMut<GameScore>,
+    mut messages: MessageReader<OverlapMessage>,
+    crystal_query: Query<&Transform, With<Crystal>>,
     player_query: Query<&Transform, With<Player>>,
-    mut worker_query: Query<(Entity, &Transform, &mut HasCrystal, &mut WorkerState), With<Worker>>,
-    crystal_query: Query<(Entity, &Transform), With<Crystal>>,
+    mut worker_query: Query<(&Transform, &mut HasCrystal, &mut WorkerState), With<Worker>>,
 ) {
-    let mut taken_crystals: HashSet<Entity> = HashSet::new();
+    let mut claims: Vec<(f32, Entity, CrystalClaim)> = Vec::new();
+
+    for overlap in messages.read() {
+        if overlap.phase == OverlapPhase::Exit {
+            continue;
+        }
+
+        let Ok(crystal_transform) = crystal_query.get(overlap.sensor) else {
+            continue;
+        };
+
+        let (collector_transform, claimant) = if let Ok(player_transform) = player_query.get(overlap.other) {
+            (player_transform, CrystalClaim::Player)
+        } else if let Ok((worker_tf, _, state)) = worker_query.get(overlap.other)
+            && *state == WorkerState::Collecting
+        {
+            (worker_tf, CrystalClaim::Worker(overlap.other))
+        } else {
+            continue;
+        };
+
+        let distance = wrapped_distance(
+            collector_transform.translation.truncate(),
+            crystal_transform.translation.truncate(),
+        );
+
+        claims.push((distance, overlap.sensor, claimant));
+    }
+
+    claims.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)).then(a.2.cmp(&b.2)));
+
+    let mut taken: HashSet<Entity> = HashSet::new();
 
-    for player_transform in player_query.iter() {
-        for (crystal_entity, crystal_transform) in crystal_query.iter() {
-            let distance = player_transform.translation.distance(crystal_transform.translation);
+    for (_, crystal_entity, claimant) in claims {
+        if taken.contains(&crystal_entity) {
+            continue;
+        }
 
-            if distance < 30.0 {
+        match claimant {
+            CrystalClaim::Player => {
                 score.0 += 200;
                 sinibombs.0 += 1;
-                commands.entity(crystal_entity).despawn();
-                taken_crystals.insert(crystal_entity);
             }
-        }
-    }
+            CrystalClaim::Worker(worker_entity) => {
+                let Ok((_, mut has_crystal, mut state)) = worker_query.get_mut(worker_entity) else {
+                    continue;
+                };
 
-    for (worker_entity, worker_tf, mut has_crystal, mut state) in &mut worker_query {
-        if *state == WorkerState::Collecting {
-            
-            for (crystal_entity, crystal_tf) in &crystal_query {
-                if taken_crystals.contains(&crystal_entity) {
+                //a worker only carries one, its further claims go to whoever is next closest
+                if has_crystal.0 {
                     continue;
                 }
 
-                if worker_tf.translation.distance(crystal_tf.translation) < 30.0 {
-                    commands.entity(crystal_entity).despawn();
-                    taken_crystals.insert(crystal_entity);
+                has_crystal.0 = true;
+                *state = WorkerState::Returning;
 
-                    has_crystal.0 = true;
-                    *state = WorkerState::Returning;
-
-                    commands.entity(worker_entity).remove::<NavigationTarget>();
-                    break;
-                }
+                commands.entity(worker_entity).remove::<NavigationTarget>();
             }
         }
+
+        taken.insert(crystal_entity);
+        commands.entity(crystal_entity).despawn();
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,175 @@
+use bevy::prelude::*;
+
+use crate::{health::*, includes::*, player::*, sensor::*, shooting::*, steering::*, warrior::*, worker::*, zone::*};
+
+//a value the director slides between as intensity goes from 0 to 1
+#[derive(Clone, Copy)]
+pub struct DirectorRange {
+    pub easiest: f32,
+    pub hardest: f32,
+}
+
+impl DirectorRange {
+    pub fn at(&self, intensity: f32) -> f32 {
+        self.easiest + (self.hardest - self.easiest) * intensity
+    }
+}
+
+//designer-set bounds and how the director reads the player
+#[derive(Resource)]
+pub struct DirectorSettings {
+    pub starting_intensity: f32,
+    //most the intensity can move in a second, so the game never lurches
+    pub adjust_rate: f32,
+    //intensity lost on the spot every time the player dies
+    pub death_relief: f32,
+    //what a player who is coping fine looks like
+    pub expected_score_rate: f32,
+    pub expected_time_since_hit: f32,
+    pub expected_sinibombs: u32,
+    //multipliers on the spawn values in warrior.rs and worker.rs
+    pub gun_cooldown: DirectorRange,
+    pub detection_radius: DirectorRange,
+    pub warrior_aggression: DirectorRange,
+    //multiplier on the zone's warrior aim error, lower is more accurate
+    pub aim_error: DirectorRange,
+    //multiplier on the zone's reinforcement intervals
+    pub spawn_interval: DirectorRange,
+}
+
+impl Default for DirectorSettings {
+    fn default() -> Self {
+        DirectorSettings {
+            starting_intensity: 0.5,
+            adjust_rate: 0.05,
+            death_relief: 0.2,
+            expected_score_rate: 100.0,
+            expected_time_since_hit: 30.0,
+            expected_sinibombs: 3,
+            gun_cooldown: DirectorRange {
+                easiest: 1.6,
+                hardest: 0.5,
+            },
+            detection_radius: DirectorRange {
+                easiest: 0.7,
+                hardest: 1.4,
+            },
+            warrior_aggression: DirectorRange {
+                easiest: 0.8,
+                hardest: 1.3,
+            },
+            aim_error: DirectorRange {
+                easiest: 1.5,
+                hardest: 0.5,
+            },
+            spawn_interval: DirectorRange {
+                easiest: 1.8,
+                hardest: 0.5,
+            },
+        }
+    }
+}
+
+//running read on how the player is doing, 0 intensity is the gentlest the game gets
+#[derive(Resource)]
+pub struct Director {
+    pub intensity: f32,
+    //smoothed points per second
+    pub score_rate: f32,
+    pub time_since_hit: f32,
+    pub deaths: u32,
+    last_score: u32,
+}
+
+impl Director {
+    pub fn new(settings: &DirectorSettings) -> Self {
+        Director {
+            intensity: settings.starting_intensity,
+            score_rate: 0.0,
+            time_since_hit: 0.0,
+            deaths: 0,
+            last_score: 0,
+        }
+    }
+}
+
+impl FromWorld for Director {
+    fn from_world(world: &mut World) -> Self {
+        Director::new(world.resource::<DirectorSettings>())
+    }
+}
+
+//seconds the score rate is averaged over
+const SCORE_RATE_SMOOTHING: f32 = 10.0;
+
+#[allow(clippy::too_many_arguments)]
+pub fn update_director(
+    time: Res<Time>,
+    settings: Res<DirectorSettings>,
+    mut director: ResMut<Director>,
+    score: Res<GameScore>,
+    sinibombs: Res<Sinibombs>,
+    mut damage_messages: MessageReader<DamageMessage>,
+    mut destroyed_messages: MessageReader<EntityDestroyedMessage>,
+    players: Query<(), With<Player>>,
+) {
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+
+    let gained = score.0.saturating_sub(director.last_score) as f32;
+    director.last_score = score.0;
+    director.score_rate += (gained / dt - director.score_rate) * (dt / SCORE_RATE_SMOOTHING).min(1.0);
+
+    director.time_since_hit += dt;
+    if damage_messages.read().any(|damage| players.contains(damage.target)) {
+        director.time_since_hit = 0.0;
+    }
+
+    //a death backs off straight away rather than waiting for the averages to notice
+    for _ in destroyed_messages.read().filter(|message| players.contains(message.entity)) {
+        director.deaths += 1;
+        director.intensity -= settings.death_relief;
+    }
+
+    //each signal is -1 when struggling, 0 when coping as expected and 1 at double that
+    let signal = |value: f32, expected: f32| (value / expected.max(f32::EPSILON)).min(2.0) - 1.0;
+    let performance = (signal(director.score_rate, settings.expected_score_rate)
+        + signal(director.time_since_hit, settings.expected_time_since_hit)
+        + signal(sinibombs.0 as f32, settings.expected_sinibombs as f32))
+        / 3.0;
+
+    let target = 0.5 + 0.5 * performance;
+    let max_step = settings.adjust_rate * dt;
+    director.intensity = (director.intensity + (target - director.intensity).clamp(-max_step, max_step)).clamp(0.0, 1.0);
+}
+
+type DirectedWarrior<'a> = (&'a mut Warrior, &'a mut OverlapSensor, &'a mut Gun, &'a mut Steering);
+
+//rescales the tunables from their spawn values, so adjustments never compound
+pub fn apply_director(
+    settings: Res<DirectorSettings>,
+    director: Res<Director>,
+    zones: Res<Zones>,
+    current_zone: Res<CurrentZone>,
+    mut warriors: Query<DirectedWarrior>,
+    mut workers: Query<&mut OverlapSensor, (With<Worker>, Without<Warrior>)>,
+) {
+    let intensity = director.intensity;
+    let detection = settings.detection_radius.at(intensity);
+    let aggression = settings.warrior_aggression.at(intensity);
+    let aim_error = scaled_aim_error(zones.get(current_zone.0).warrior_aim_error, settings.aim_error.at(intensity));
+
+    for (mut warrior, mut sensor, mut gun, mut steering) in &mut warriors {
+        warrior.aim_error = aim_error;
+        sensor.radius = WARRIOR_DETECTION_RADIUS * detection;
+        gun.cooldown = WARRIOR_GUN_COOLDOWN * settings.gun_cooldown.at(intensity);
+        steering.max_speed = WARRIOR_MAX_SPEED * aggression;
+        steering.max_force = WARRIOR_MAX_FORCE * aggression;
+    }
+
+    for mut sensor in &mut workers {
+        sensor.radius = WORKER_DETECTION_RADIUS * detection;
+    }
+}
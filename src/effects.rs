@@ -0,0 +1,159 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::{health::*, physics::*, shooting::*};
+
+//collisions softer than this don't throw sparks
+const SPARK_MIN_IMPULSE: f32 = 200.0;
+
+//short lived ring that grows and disappears, used for impacts and explosions
+#[derive(Component)]
+pub struct Spark {
+    pub timer: f32,
+    pub lifetime: f32,
+    pub growth: f32,
+}
+
+//fragment thrown out when something is destroyed, it has no collider so it never hits anything
+#[derive(Component)]
+pub struct Debris {
+    pub lifetime: f32,
+}
+
+pub fn spawn_spark(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    position: Vec2,
+    radius: f32,
+    lifetime: f32,
+    color: Color,
+) {
+    commands.spawn((
+        Spark {
+            timer: lifetime,
+            lifetime,
+            growth: 3.0,
+        },
+        Transform::from_translation(position.extend(5.0)),
+        Mesh2d(meshes.add(Annulus::new(radius * 0.8, radius))),
+        MeshMaterial2d(materials.add(ColorMaterial::from(color))),
+    ));
+}
+
+pub fn spawn_destroy_effects(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut messages: MessageReader<EntityDestroyedMessage>,
+) {
+    let mut rng = rand::rng();
+
+    for message in messages.read() {
+        spawn_spark(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            message.position,
+            12.0,
+            0.4,
+            Color::srgb(1.0, 0.6, 0.1),
+        );
+
+        for _ in 0..5 {
+            let direction = Vec2::from_angle(rng.random_range(0.0..std::f32::consts::TAU));
+            let speed = rng.random_range(60.0..160.0);
+
+            commands.spawn((
+                Debris {
+                    lifetime: rng.random_range(0.5..1.0),
+                },
+                Velocity(direction * speed),
+                AngularVelocity(rng.random_range(-12.0..12.0)),
+                Transform::from_translation(message.position.extend(4.0)),
+                Mesh2d(meshes.add(Triangle2d::new(
+                    Vec2::new(0.0, 3.0),
+                    Vec2::new(-2.0, -2.0),
+                    Vec2::new(2.0, -2.0),
+                ))),
+                MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgb(0.7, 0.7, 0.7)))),
+            ));
+        }
+    }
+}
+
+pub fn update_debris(mut commands: Commands, time: Res<Time>, mut query: Query<(Entity, &mut Debris)>) {
+    for (entity, mut debris) in &mut query {
+        debris.lifetime -= time.delta_secs();
+        if debris.lifetime <= 0.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+pub fn spawn_hit_sparks(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut messages: MessageReader<ProjectileHitMessage>,
+) {
+    for hit in messages.read() {
+        spawn_spark(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            hit.position,
+            6.0,
+            0.15,
+            Color::srgb(1.0, 1.0, 0.6),
+        );
+    }
+}
+
+pub fn spawn_collision_sparks(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut messages: MessageReader<CollisionMessage>,
+    bodies: Query<(&Transform, &Collider)>,
+) {
+    for message in messages.read() {
+        if message.impulse < SPARK_MIN_IMPULSE {
+            continue;
+        }
+
+        let (Ok((a_transform, a_collider)), Ok(_)) = (bodies.get(message.a), bodies.get(message.b)) else {
+            continue;
+        };
+
+        //contact point sits on the surface of `a`, facing `b`
+        let contact = a_transform.translation.truncate() + message.normal * a_collider.radius;
+
+        spawn_spark(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            contact,
+            4.0,
+            0.2,
+            Color::srgb(0.9, 0.9, 1.0),
+        );
+    }
+}
+
+pub fn update_sparks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Transform, &mut Spark)>,
+) {
+    for (entity, mut transform, mut spark) in &mut query {
+        spark.timer -= time.delta_secs();
+        if spark.timer <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let progress = 1.0 - spark.timer / spark.lifetime;
+        transform.scale = Vec3::splat(1.0 + progress * spark.growth);
+    }
+}
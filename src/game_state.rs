@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+
+use crate::{director::*, effects::*, includes::*, physics::*, player::*, reinforcement::*, zone::*};
+
+#[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum GameState {
+    #[default]
+    Title,
+    Playing,
+    Paused,
+    GameOver,
+}
+
+pub fn game_state_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    match state.get() {
+        GameState::Title => {
+            if keyboard.just_pressed(KeyCode::Enter) {
+                next_state.set(GameState::Playing);
+            }
+        }
+        GameState::Playing => {
+            if keyboard.just_pressed(KeyCode::Escape) {
+                next_state.set(GameState::Paused);
+            }
+        }
+        GameState::Paused => {
+            if keyboard.just_pressed(KeyCode::Escape) {
+                next_state.set(GameState::Playing);
+            } else if keyboard.just_pressed(KeyCode::KeyQ) {
+                next_state.set(GameState::Title);
+            }
+        }
+        GameState::GameOver => {
+            if keyboard.just_pressed(KeyCode::Enter) {
+                next_state.set(GameState::Playing);
+            } else if keyboard.just_pressed(KeyCode::Escape) {
+                next_state.set(GameState::Title);
+            }
+        }
+    }
+}
+
+//every gameplay entity moves, so anything with a velocity belongs to the world
+//(sinistar pieces are children and go with their parent), along with the player's controller
+type WorldEntityFilter = (
+    Or<(With<Velocity>, With<DeathExplosion>, With<Spark>, With<PlayerController>)>,
+    Without<ChildOf>,
+);
+
+pub fn cleanup_world(mut commands: Commands, query: Query<Entity, WorldEntityFilter>) {
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn reset_game(
+    mut score: ResMut<GameScore>,
+    mut sinibombs: ResMut<Sinibombs>,
+    mut lives: ResMut<Lives>,
+    mut respawn: ResMut<PlayerRespawn>,
+    mut current_zone: ResMut<CurrentZone>,
+    mut reinforcements: ResMut<Reinforcements>,
+    mut director: ResMut<Director>,
+    director_settings: Res<DirectorSettings>,
+) {
+    *score = GameScore::default();
+    *sinibombs = Sinibombs::default();
+    *lives = Lives::default();
+    *respawn = PlayerRespawn::default();
+    *current_zone = CurrentZone::default();
+    *reinforcements = Reinforcements::default();
+    *director = Director::new(&director_settings);
+}
@@ -1,4 +1,194 @@
 use bevy::prelude::*;
 
+use crate::{physics::*, player::*};
+
+//change in speed from a collision that costs one point of health, gentle bumps do nothing
+const COLLISION_DAMAGE_SPEED: f32 = 800.0;
+
+#[derive(Component)]
+pub struct Health(pub i32);
+
+#[derive(Component)]
+pub struct MaxHealth(pub i32);
+
+//flat reduction applied to every hit except explosions
+#[derive(Component)]
+pub struct Armor(pub i32);
+
+//heals back up to MaxHealth once the entity has gone `delay` seconds without being hurt
 #[derive(Component)]
-pub struct Health(pub i32);
\ No newline at end of file
+pub struct Regeneration {
+    pub per_second: f32,
+    pub delay: f32,
+    pub cooldown: f32,
+    pub progress: f32,
+}
+
+impl Regeneration {
+    pub fn new(per_second: f32, delay: f32) -> Self {
+        Regeneration {
+            per_second,
+            delay,
+            cooldown: 0.0,
+            progress: 0.0,
+        }
+    }
+}
+
+//damage wears it down to nothing but never destroys it, like a sinistar still being built
+#[derive(Component)]
+pub struct Indestructible;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DamageKind {
+    Projectile,
+    Collision,
+    Explosion,
+}
+
+//every source of harm goes through this, apply_damage is the only thing that touches Health
+#[derive(Message)]
+pub struct DamageMessage {
+    //None for damage nobody is responsible for
+    pub source: Option<Entity>,
+    pub target: Entity,
+    pub amount: i32,
+    pub kind: DamageKind,
+}
+
+//the part of a DamageMessage that actually came off Health, after armor and anything already at zero
+#[derive(Message)]
+pub struct DamageDealtMessage {
+    pub source: Option<Entity>,
+    pub target: Entity,
+    pub amount: i32,
+}
+
+#[derive(Message)]
+pub struct EntityDestroyedMessage {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub killer: Option<Entity>,
+}
+
+type DamageTarget<'a> = (
+    &'a Transform,
+    &'a mut Health,
+    Option<&'a Armor>,
+    Option<&'a mut Regeneration>,
+    Has<Invulnerable>,
+    Has<Indestructible>,
+);
+
+pub fn apply_damage(
+    mut messages: MessageReader<DamageMessage>,
+    mut targets: Query<DamageTarget>,
+    mut dealt_writer: MessageWriter<DamageDealtMessage>,
+    mut writer: MessageWriter<EntityDestroyedMessage>,
+) {
+    for damage in messages.read() {
+        let Ok((transform, mut health, armor, regeneration, invulnerable, indestructible)) = targets.get_mut(damage.target)
+        else {
+            continue;
+        };
+
+        if invulnerable {
+            continue;
+        }
+
+        let mut amount = damage.amount;
+        if let Some(armor) = armor
+            && damage.kind != DamageKind::Explosion
+        {
+            amount = (amount - armor.0).max(0);
+        }
+
+        if let Some(mut regeneration) = regeneration {
+            regeneration.cooldown = regeneration.delay;
+            regeneration.progress = 0.0;
+        }
+
+        let dealt = amount.min(health.0.max(0));
+        if dealt > 0 {
+            dealt_writer.write(DamageDealtMessage {
+                source: damage.source,
+                target: damage.target,
+                amount: dealt,
+            });
+        }
+
+        if indestructible {
+            health.0 = (health.0 - amount).max(0);
+            continue;
+        }
+
+        //only report the hit that takes it over the edge, later hits in the same tick are ignored
+        let was_alive = health.0 > 0;
+        health.0 -= amount;
+
+        if was_alive && health.0 <= 0 {
+            writer.write(EntityDestroyedMessage {
+                entity: damage.target,
+                position: transform.translation.truncate(),
+                killer: damage.source,
+            });
+        }
+    }
+}
+
+//hard knocks hurt both bodies, each is blamed on the other.
+//damage goes by how sharply a body's speed changed, so heavy rocks grinding together stay intact
+pub fn collision_damage(
+    mut messages: MessageReader<CollisionMessage>,
+    masses: Query<&Mass>,
+    controllers: Query<&ControlledBy>,
+    mut writer: MessageWriter<DamageMessage>,
+) {
+    for collision in messages.read() {
+        for (target, source) in [(collision.a, collision.b), (collision.b, collision.a)] {
+            let Ok(mass) = masses.get(target) else {
+                continue;
+            };
+
+            let amount = (collision.impulse / mass.0 / COLLISION_DAMAGE_SPEED) as i32;
+            if amount <= 0 {
+                continue;
+            }
+
+            writer.write(DamageMessage {
+                source: Some(ControlledBy::credit(source, controllers.get(source).ok())),
+                target,
+                amount,
+                kind: DamageKind::Collision,
+            });
+        }
+    }
+}
+
+pub fn regeneration_system(time: Res<Time>, mut query: Query<(&mut Health, &MaxHealth, &mut Regeneration)>) {
+    let dt = time.delta_secs();
+
+    for (mut health, max_health, mut regeneration) in &mut query {
+        if health.0 <= 0 || health.0 >= max_health.0 {
+            continue;
+        }
+
+        if regeneration.cooldown > 0.0 {
+            regeneration.cooldown -= dt;
+            continue;
+        }
+
+        regeneration.progress += regeneration.per_second * dt;
+        while regeneration.progress >= 1.0 && health.0 < max_health.0 {
+            regeneration.progress -= 1.0;
+            health.0 += 1;
+        }
+    }
+}
+
+//the single place destroyed entities leave the world, score and effects read the same message first
+pub fn despawn_destroyed(mut commands: Commands, mut messages: MessageReader<EntityDestroyedMessage>) {
+    for message in messages.read() {
+        commands.entity(message.entity).despawn();
+    }
+}
@@ -2,6 +2,15 @@ use bevy::prelude::*;
 
 pub const PLAYER_ROT_SPEED: f32 = 3.5;
 pub const PLAYER_DAMPING: f32 = 0.985;
+//only used with inertial rotation handling
+pub const PLAYER_TURN_ACCELERATION: f32 = 12.0;
+pub const PLAYER_ANGULAR_DAMPING: f32 = 0.94;
+pub const PLAYER_STARTING_LIVES: u32 = 3;
+pub const PLAYER_RESPAWN_DELAY: f32 = 2.0;
+pub const PLAYER_INVULNERABILITY_TIME: f32 = 3.0;
+
+//how many times a second the simulation steps when nothing overrides it
+const DEFAULT_TICK_RATE: f64 = 60.0;
 
 //the camera sees 750x1000
 //the world is 4000x4000, meaning there is roughly 3000 pixels of off-screen space that you have to traverse before you see an object loop around
@@ -12,4 +21,32 @@ pub const WORLD_HEIGHT: f32 = 4000.0;
 pub struct GameScore(pub u32);
 
 #[derive(Resource, Default)]
-pub struct Sinibombs(pub u32);
\ No newline at end of file
+pub struct Sinibombs(pub u32);
+
+#[derive(Resource)]
+pub struct Lives(pub u32);
+
+impl Default for Lives {
+    fn default() -> Self {
+        Lives(PLAYER_STARTING_LIVES)
+    }
+}
+
+//how the fixed timestep runs, rendering interpolates in between ticks.
+//SINIRUST_TICK_RATE overrides the rate at launch and changing the resource retimes a running game
+#[derive(Resource, Clone, Copy)]
+pub struct SimulationSettings {
+    pub tick_rate: f64,
+}
+
+impl Default for SimulationSettings {
+    fn default() -> Self {
+        let tick_rate = std::env::var("SINIRUST_TICK_RATE")
+            .ok()
+            .and_then(|value| value.parse::<f64>().ok())
+            .filter(|rate| *rate > 0.0)
+            .unwrap_or(DEFAULT_TICK_RATE);
+
+        SimulationSettings { tick_rate }
+    }
+}
@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+
+use crate::{includes::*, topology::*};
+
+//simulated transform at the start and end of the last fixed tick.
+//between ticks the rendered Transform is blended between the two
+#[derive(Component, Default)]
+pub struct TransformInterpolation {
+    start: Option<(Vec3, Quat)>,
+    end: Option<(Vec3, Quat)>,
+}
+
+//puts the simulated transform back before the fixed loop so ticks never see a blended value
+pub fn restore_simulated_transforms(mut query: Query<(&mut Transform, &TransformInterpolation)>) {
+    for (mut transform, interpolation) in &mut query {
+        if let Some((translation, rotation)) = interpolation.end {
+            transform.translation = translation;
+            transform.rotation = rotation;
+        }
+    }
+}
+
+pub fn record_tick_start(mut query: Query<(&Transform, &mut TransformInterpolation)>) {
+    for (transform, mut interpolation) in &mut query {
+        interpolation.start = Some((transform.translation, transform.rotation));
+    }
+}
+
+pub fn record_tick_end(mut query: Query<(&Transform, &mut TransformInterpolation)>) {
+    for (transform, mut interpolation) in &mut query {
+        let end = (transform.translation, transform.rotation);
+
+        //re-express the start in the same copy of the world so wrapping doesn't streak across the screen
+        let start = interpolation.start.map_or(end, |(translation, rotation)| {
+            let offset = wrapped_delta(translation.truncate(), end.0.truncate());
+            (end.0 - offset.extend(end.0.z - translation.z), rotation)
+        });
+
+        interpolation.start = Some(start);
+        interpolation.end = Some(end);
+    }
+}
+
+pub fn interpolate_transforms(
+    time: Res<Time<Fixed>>,
+    mut query: Query<(&mut Transform, &TransformInterpolation)>,
+) {
+    let alpha = time.overstep_fraction();
+
+    for (mut transform, interpolation) in &mut query {
+        let (Some(start), Some(end)) = (interpolation.start, interpolation.end) else {
+            continue;
+        };
+
+        transform.translation = start.0.lerp(end.0, alpha);
+        transform.rotation = start.1.slerp(end.1, alpha);
+    }
+}
+
+pub fn apply_simulation_settings(settings: Res<SimulationSettings>, mut time: ResMut<Time<Fixed>>) {
+    if settings.is_changed() {
+        time.set_timestep_hz(settings.tick_rate);
+    }
+}
@@ -1,34 +1,64 @@
 mod asteroid;
 mod audio;
 mod crystal;
+mod director;
+mod effects;
+mod game_state;
 mod health;
 mod includes;
+mod interpolation;
 mod navigation;
 mod physics;
 mod player;
+mod reinforcement;
+mod scoring;
+mod sensor;
 mod shooting;
+mod sinibomb;
+mod sinistar;
+mod spatial;
+mod steering;
 mod team;
+mod topology;
 mod ui;
 mod warrior;
 mod worker;
+mod zone;
 
 use asteroid::*;
 use audio::*;
+use bevy::ecs::{schedule::ScheduleConfigs, system::ScheduleSystem};
 use bevy::math::*;
 use bevy::prelude::*;
 use crystal::*;
+use director::*;
+use effects::*;
+use game_state::*;
+use health::*;
 use includes::*;
+use interpolation::*;
 use physics::*;
 use player::*;
+use reinforcement::*;
+use scoring::*;
+use sensor::*;
 use shooting::*;
+use sinibomb::*;
+use sinistar::*;
+use spatial::*;
+use steering::*;
+use topology::*;
 use ui::*;
 use warrior::*;
 use worker::*;
+use zone::*;
 
 #[derive(Component)]
 struct MainCamera;
 
 fn main() {
+    let simulation = SimulationSettings::default();
+
     let mut app = App::new();
     app.add_plugins(DefaultPlugins.set(WindowPlugin {
         primary_window: Some(Window {
@@ -41,53 +71,165 @@ fn main() {
     }))
     .init_resource::<GameScore>()
     .init_resource::<Sinibombs>()
+    .init_resource::<Lives>()
+    .init_resource::<PlayerRespawn>()
+    .init_resource::<RotationHandling>()
+    .init_resource::<Zones>()
+    .init_resource::<CurrentZone>()
+    .init_resource::<SpatialGrid>()
+    .init_resource::<Reinforcements>()
+    .init_resource::<DirectorSettings>()
+    .init_resource::<Director>()
+    .insert_resource(Time::<Fixed>::from_hz(simulation.tick_rate))
+    .insert_resource(simulation)
+    .init_state::<GameState>()
+    .add_systems(Startup, (validate_zones, load_sounds, setup, setup_score_ui).chain())
+    .add_systems(OnEnter(GameState::Title), (cleanup_world, spawn_title_screen))
+    .add_systems(OnEnter(GameState::Paused), spawn_pause_overlay)
+    .add_systems(OnEnter(GameState::GameOver), spawn_game_over_overlay)
+    .add_systems(
+        OnTransition {
+            exited: GameState::Title,
+            entered: GameState::Playing,
+        },
+        start_game(),
+    )
+    .add_systems(
+        OnTransition {
+            exited: GameState::GameOver,
+            entered: GameState::Playing,
+        },
+        start_game(),
+    )
     .add_systems(
-        Startup,
+        RunFixedMainLoop,
         (
-            load_sounds,
-            setup,
-            spawn_asteroids,
-            spawn_workers,
-            spawn_warriors,
-            setup_score_ui,
-        )
-            .chain(),
+            restore_simulated_transforms.in_set(RunFixedMainLoopSystems::BeforeFixedMainLoop),
+            interpolate_transforms.in_set(RunFixedMainLoopSystems::AfterFixedMainLoop),
+        ),
     )
+    .add_systems(FixedFirst, record_tick_start)
+    .add_systems(FixedLast, record_tick_end)
     .add_systems(
-        Update,
+        FixedUpdate,
         (
-            player_movement_input,
-            worker_roaming_ai,
-            worker_sensor_ai,
-            worker_movement,
-            warrior_ai,
-            warrior_movement,
+            (
+                apply_director,
+                player_movement_input,
+                worker_roaming_ai,
+                worker_sensor_ai,
+                worker_flee_ai,
+                worker_return_ai,
+                warrior_ai,
+                sinistar_ai,
+                (
+                    seek_behaviour,
+                    arrive_behaviour,
+                    pursue_behaviour,
+                    flee_behaviour,
+                    evade_behaviour,
+                    wander_behaviour,
+                    separation_behaviour,
+                    obstacle_avoidance_behaviour,
+                ),
+                apply_steering,
+                warrior_aiming,
+            )
+                .chain(),
             //worker_collection_check,
-            apply_velocity,
-            handle_collisions,
-            crystal_impacts,
-            player_shooting_input,
-            gun_system,
-            projectile_system,
-            update_score_text,
-            camera_follow,
+            (
+                apply_velocity,
+                apply_angular_velocity,
+                rebuild_spatial_grid,
+                update_sensors,
+                handle_collisions,
+                (collision_damage, spawn_collision_sparks),
+            )
+                .chain(),
+            (
+                crystal_impacts,
+                sinistar_delivery,
+                sync_sinistar_pieces,
+                sinistar_attack,
+                player_shooting_input,
+                gun_system,
+                projectile_system,
+                (projectile_damage_system, spawn_crystals_from_hits, spawn_hit_sparks),
+                bomb_launcher_system,
+                sinibomb_homing,
+                sinibomb_impacts,
+                advance_zone,
+            )
+                .chain(),
+            (
+                apply_damage,
+                (
+                    award_damage_score,
+                    award_destroy_score,
+                    spawn_destroy_effects,
+                    fragment_asteroids,
+                    detonate_asteroids,
+                    player_death_system,
+                    sinistar_destroyed,
+                    update_director,
+                ),
+                despawn_destroyed,
+                regeneration_system,
+                player_respawn_system,
+                spawn_reinforcements,
+                invulnerability_system,
+                update_debris,
+            )
+                .chain(),
             wrap_around_camera,
         )
-            .chain(),
+            .chain()
+            .run_if(in_state(GameState::Playing)),
+    )
+    .add_systems(
+        Update,
+        (player_bomb_input, rotation_handling_input, death_explosion_system, update_sparks, camera_follow)
+            .run_if(in_state(GameState::Playing)),
+    )
+    .add_systems(
+        Update,
+        (
+            game_state_input,
+            update_score_text,
+            update_sinibombs_text,
+            update_lives_text,
+            update_zone_text,
+            apply_simulation_settings,
+        ),
     )
     .add_message::<ShootMessage>()
+    .add_message::<CollisionMessage>()
+    .add_message::<OverlapMessage>()
+    .add_message::<ProjectileHitMessage>()
+    .add_message::<DamageMessage>()
+    .add_message::<DamageDealtMessage>()
+    .add_message::<EntityDestroyedMessage>()
+    .add_message::<LaunchBombMessage>()
+    .add_message::<SinistarDestroyedMessage>()
     .run();
 }
 
-fn setup(
-    mut commands: Commands,
-    meshes: ResMut<Assets<Mesh>>,
-    materials: ResMut<Assets<ColorMaterial>>,
-    sounds: Res<AudioAssets>,
-) {
-    commands.spawn((Camera2d, MainCamera));
+//tears down whatever the previous run left behind and builds a fresh world
+fn start_game() -> ScheduleConfigs<ScheduleSystem> {
+    (
+        cleanup_world,
+        reset_game,
+        setup_player,
+        spawn_asteroids,
+        spawn_workers,
+        spawn_warriors,
+        spawn_sinistar,
+    )
+        .chain()
+}
 
-    spawn_player(&mut commands, meshes, materials);
+fn setup(mut commands: Commands, sounds: Res<AudioAssets>) {
+    commands.spawn((Camera2d, MainCamera));
 
     spawn_music(&mut commands, sounds.music.clone());
 }
@@ -95,16 +237,30 @@ fn setup(
 fn player_movement_input(
     keyboard: Res<ButtonInput<KeyCode>>,
     time: Res<Time>,
-    mut query: Query<(&mut Transform, &Player, &mut Velocity)>,
+    handling: Res<RotationHandling>,
+    mut query: Query<(&Transform, &Player, &mut Velocity, &mut AngularVelocity)>,
 ) {
-    if let Ok((mut transform, player, mut velocity)) = query.single_mut() {
+    if let Ok((transform, player, mut velocity, mut angular_velocity)) = query.single_mut() {
         let dt = time.delta_secs();
 
+        let mut turn = 0.0;
         if keyboard.pressed(KeyCode::KeyA) {
-            transform.rotate_z(PLAYER_ROT_SPEED * dt);
+            turn += 1.0;
         }
         if keyboard.pressed(KeyCode::KeyD) {
-            transform.rotate_z(-PLAYER_ROT_SPEED * dt);
+            turn -= 1.0;
+        }
+
+        match *handling {
+            RotationHandling::Direct => **angular_velocity = turn * PLAYER_ROT_SPEED,
+            RotationHandling::Inertial => {
+                **angular_velocity += turn * PLAYER_TURN_ACCELERATION * dt;
+                **angular_velocity *= PLAYER_ANGULAR_DAMPING.powf(dt * 90.0);
+                //collisions can spin the ship faster than the thrusters ever could
+                if turn != 0.0 {
+                    **angular_velocity = angular_velocity.clamp(-PLAYER_ROT_SPEED, PLAYER_ROT_SPEED);
+                }
+            }
         }
         if keyboard.pressed(KeyCode::KeyW) {
             let forward = (transform.rotation * Vec3::Y).truncate();
@@ -119,15 +275,37 @@ fn player_movement_input(
     }
 }
 
+//switches between direct and inertial turning
+fn rotation_handling_input(keyboard: Res<ButtonInput<KeyCode>>, mut handling: ResMut<RotationHandling>) {
+    if keyboard.just_pressed(KeyCode::KeyR) {
+        *handling = match *handling {
+            RotationHandling::Direct => RotationHandling::Inertial,
+            RotationHandling::Inertial => RotationHandling::Direct,
+        };
+    }
+}
+
 fn player_shooting_input(
     keyboard: Res<ButtonInput<KeyCode>>,
     query: Query<Entity, With<Player>>,
     mut writer: MessageWriter<ShootMessage>,
 ) {
-    if keyboard.pressed(KeyCode::Space) {
-        if let Ok(entity) = query.single() {
-            writer.write(ShootMessage { entity });
-        }
+    if keyboard.pressed(KeyCode::Space)
+        && let Ok(entity) = query.single()
+    {
+        writer.write(ShootMessage { entity });
+    }
+}
+
+fn player_bomb_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    query: Query<Entity, With<Player>>,
+    mut writer: MessageWriter<LaunchBombMessage>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyE)
+        && let Ok(entity) = query.single()
+    {
+        writer.write(LaunchBombMessage { entity });
     }
 }
 
@@ -135,11 +313,11 @@ fn camera_follow(
     player_query: Query<&Transform, With<Player>>,
     mut camera_query: Query<&mut Transform, (With<MainCamera>, Without<Player>)>,
 ) {
-    if let Ok(player_transform) = player_query.single() {
-        if let Ok(mut camera_transform) = camera_query.single_mut() {
-            camera_transform.translation.x = player_transform.translation.x;
-            camera_transform.translation.y = player_transform.translation.y;
-        }
+    if let Ok(player_transform) = player_query.single()
+        && let Ok(mut camera_transform) = camera_query.single_mut()
+    {
+        camera_transform.translation.x = player_transform.translation.x;
+        camera_transform.translation.y = player_transform.translation.y;
     }
 }
 
@@ -152,23 +330,12 @@ fn wrap_around_camera(
     };
     let cam_pos = camera_transform.translation.truncate();
 
-    let half_width = WORLD_WIDTH / 2.0;
-    let half_height = WORLD_HEIGHT / 2.0;
-
+    //keep every object in the copy of the world the camera is looking at
     for mut obj_transform in &mut object_query {
         let obj_pos = obj_transform.translation.truncate();
-        let diff = obj_pos - cam_pos;
-
-        if diff.x > half_width {
-            obj_transform.translation.x -= WORLD_WIDTH;
-        } else if diff.x < -half_width {
-            obj_transform.translation.x += WORLD_WIDTH;
-        }
+        let wrapped = wrap_around(obj_pos, cam_pos);
 
-        if diff.y > half_height {
-            obj_transform.translation.y -= WORLD_HEIGHT;
-        } else if diff.y < -half_height {
-            obj_transform.translation.y += WORLD_HEIGHT;
-        }
+        obj_transform.translation.x = wrapped.x;
+        obj_transform.translation.y = wrapped.y;
     }
 }
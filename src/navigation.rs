@@ -1,4 +1,8 @@
 use bevy::prelude::*;
 
 #[derive(Component)]
-pub struct NavigationTarget(pub Vec2);
\ No newline at end of file
+pub struct NavigationTarget(pub Vec2);
+
+//point the entity is turning to face instead of its direction of travel
+#[derive(Component)]
+pub struct AimTarget(pub Vec2);
\ No newline at end of file
@@ -1,8 +1,18 @@
 use bevy::prelude::*;
 
+use crate::{interpolation::*, spatial::*, topology::*};
+
+//anything that moves is simulated on the fixed tick and drawn interpolated
 #[derive(Component, Deref, DerefMut)]
+#[require(TransformInterpolation)]
 pub struct Velocity(pub Vec2);
 
+//spin in radians per second, counter-clockwise positive.
+//bodies without it never rotate from collisions
+#[derive(Component, Deref, DerefMut, Default)]
+#[require(TransformInterpolation)]
+pub struct AngularVelocity(pub f32);
+
 #[derive(Component)]
 pub struct Collider {
     pub radius: f32,
@@ -11,29 +21,150 @@ pub struct Collider {
 #[derive(Component)]
 pub struct Mass(pub f32);
 
+impl Mass {
+    //every collider is a circle, so treat the body as a solid disc
+    pub fn moment_of_inertia(&self, collider: &Collider) -> f32 {
+        0.5 * self.0 * collider.radius * collider.radius
+    }
+}
+
 #[derive(Component)]
 pub struct WrapsAroundCamera;
 
+//collision layer bits, an entity can be a member of several
+pub const LAYER_PLAYER: u32 = 1 << 0;
+pub const LAYER_ENEMY: u32 = 1 << 1;
+pub const LAYER_ASTEROID: u32 = 1 << 2;
+pub const LAYER_PICKUP: u32 = 1 << 3;
+pub const LAYER_PROJECTILE: u32 = 1 << 4;
+pub const LAYER_SINIBOMB: u32 = 1 << 5;
+pub const LAYER_ALL: u32 = u32::MAX;
+
+//which layers a collider is on and which it reacts to.
+//two colliders only interact when each one's filters include the other's memberships
+#[derive(Component, Clone, Copy)]
+pub struct CollisionLayers {
+    pub memberships: u32,
+    pub filters: u32,
+}
+
+impl CollisionLayers {
+    pub const fn new(memberships: u32, filters: u32) -> Self {
+        CollisionLayers { memberships, filters }
+    }
+
+    pub fn interacts_with(&self, other: &CollisionLayers) -> bool {
+        self.memberships & other.filters != 0 && other.memberships & self.filters != 0
+    }
+}
+
+//colliders without layers meet everything
+impl Default for CollisionLayers {
+    fn default() -> Self {
+        CollisionLayers::new(LAYER_ALL, LAYER_ALL)
+    }
+}
+
+//detected but never pushed around or pushing, for pickups and projectiles
+#[derive(Component)]
+pub struct NonSolid;
+
+//how much grip surfaces have on each other, this is what turns a glancing hit into spin
+const COLLISION_FRICTION: f32 = 0.3;
+
+//two bodies touched, `normal` points from `a` to `b`
+#[derive(Message)]
+pub struct CollisionMessage {
+    pub a: Entity,
+    pub b: Entity,
+    pub normal: Vec2,
+    pub impulse: f32,
+}
+
+//fraction of `motion` at which a circle starting at the origin first touches one at `offset`,
+//`radius` is the two radii combined. None if they never touch during the move
+pub fn swept_circle_hit(motion: Vec2, offset: Vec2, radius: f32) -> Option<f32> {
+    let c = offset.length_squared() - radius * radius;
+    if c <= 0.0 {
+        return Some(0.0);
+    }
+
+    let a = motion.length_squared();
+    if a <= f32::EPSILON {
+        return None;
+    }
+
+    let b = -2.0 * motion.dot(offset);
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let t = (-b - discriminant.sqrt()) / (2.0 * a);
+    (0.0..=1.0).contains(&t).then_some(t)
+}
+
 pub fn apply_velocity(time: Res<Time>, mut query: Query<(&Velocity, &mut Transform)>) {
     for (velocity, mut transform) in &mut query {
         transform.translation += Vec3::new(velocity.x, velocity.y, 0.0) * time.delta_secs();
     }
 }
 
-pub fn handle_collisions(mut query: Query<(&mut Transform, &mut Velocity, &Collider, &Mass)>) {
-    //iter_combinations_mut ensures we check A vs B, but not A vs A or B vs A again
-    let mut combinations = query.iter_combinations_mut();
+pub fn apply_angular_velocity(time: Res<Time>, mut query: Query<(&AngularVelocity, &mut Transform)>) {
+    for (angular_velocity, mut transform) in &mut query {
+        transform.rotate_z(angular_velocity.0 * time.delta_secs());
+    }
+}
+
+//2d cross product of a scalar spin with a lever arm, the surface velocity it causes
+fn spin_velocity(angular_velocity: f32, arm: Vec2) -> Vec2 {
+    Vec2::new(-angular_velocity * arm.y, angular_velocity * arm.x)
+}
+
+type PhysicsBody<'a> = (
+    &'a mut Transform,
+    &'a mut Velocity,
+    &'a Collider,
+    &'a Mass,
+    Option<&'a CollisionLayers>,
+    Option<&'a mut AngularVelocity>,
+);
+
+pub fn handle_collisions(
+    grid: Res<SpatialGrid>,
+    mut query: Query<PhysicsBody, Without<NonSolid>>,
+    mut writer: MessageWriter<CollisionMessage>,
+) {
+    //the grid gives us nearby candidates, ordering the entities ensures we check A vs B but not B vs A again
+    let mut pairs: Vec<(Entity, Entity)> = Vec::new();
+    for entry in grid.entries() {
+        for other in grid.query(entry.position, entry.radius) {
+            if entry.entity < other.entity {
+                pairs.push((entry.entity, other.entity));
+            }
+        }
+    }
+
+    for (e1, e2) in pairs {
+        //colliders without mass, like sinibombs, are not part of the physics pass
+        let Ok([(mut t1, mut v1, c1, m1, l1, mut w1), (mut t2, mut v2, c2, m2, l2, mut w2)]) = query.get_many_mut([e1, e2])
+        else {
+            continue;
+        };
+
+        if !l1.copied().unwrap_or_default().interacts_with(&l2.copied().unwrap_or_default()) {
+            continue;
+        }
 
-    while let Some([(mut t1, mut v1, c1, m1), (mut t2, mut v2, c2, m2)]) = combinations.fetch_next()
-    {
         let p1 = t1.translation.truncate();
         let p2 = t2.translation.truncate();
 
-        let distance = p1.distance(p2);
+        let offset = wrapped_delta(p1, p2);
+        let distance = offset.length();
         let min_dist = c1.radius + c2.radius;
 
         if distance < min_dist {
-            let normal = (p2 - p1).normalize_or_zero();
+            let normal = offset.normalize_or_zero();
 
             let depth = min_dist - distance;
             let separation = normal * (depth / 2.0);
@@ -41,18 +172,62 @@ pub fn handle_collisions(mut query: Query<(&mut Transform, &mut Velocity, &Colli
             t1.translation -= separation.extend(0.0);
             t2.translation += separation.extend(0.0);
 
-            let v_rel = v1.0 - v2.0;
+            //lever arms from each centre to the contact point
+            let r1 = normal * c1.radius;
+            let r2 = -normal * c2.radius;
+            let spin1 = w1.as_deref().map_or(0.0, |w| w.0);
+            let spin2 = w2.as_deref().map_or(0.0, |w| w.0);
+
+            //velocity of b's surface relative to a's at the contact, spin included
+            let v_rel = (v2.0 + spin_velocity(spin2, r2)) - (v1.0 + spin_velocity(spin1, r1));
             let vel_along_normal = v_rel.dot(normal);
 
-            if vel_along_normal > 0.0 {
+            //already moving apart
+            if vel_along_normal >= 0.0 {
+                writer.write(CollisionMessage {
+                    a: e1,
+                    b: e2,
+                    normal,
+                    impulse: 0.0,
+                });
                 continue;
             }
 
-            let j = -(2.0 * vel_along_normal) / (1.0 / m1.0 + 1.0 / m2.0);
-            let impulse = j * normal;
+            let inv_mass1 = 1.0 / m1.0;
+            let inv_mass2 = 1.0 / m2.0;
+            //bodies that can't spin act as if they had infinite inertia
+            let inv_inertia1 = if w1.is_some() { 1.0 / m1.moment_of_inertia(c1) } else { 0.0 };
+            let inv_inertia2 = if w2.is_some() { 1.0 / m2.moment_of_inertia(c2) } else { 0.0 };
+
+            let j = -(2.0 * vel_along_normal) / (inv_mass1 + inv_mass2);
+
+            //the normal passes through both centres, so only the sliding part of the hit can spin them
+            let tangent = normal.perp();
+            let vel_along_tangent = v_rel.dot(tangent);
+            let tangent_mass = inv_mass1
+                + inv_mass2
+                + r1.perp_dot(tangent).powi(2) * inv_inertia1
+                + r2.perp_dot(tangent).powi(2) * inv_inertia2;
+            let jt = (-vel_along_tangent / tangent_mass).clamp(-COLLISION_FRICTION * j, COLLISION_FRICTION * j);
 
-            v1.0 += impulse / m1.0;
-            v2.0 -= impulse / m2.0;
+            let impulse = j * normal + jt * tangent;
+
+            v1.0 -= impulse * inv_mass1;
+            v2.0 += impulse * inv_mass2;
+
+            if let Some(w1) = w1.as_deref_mut() {
+                w1.0 -= r1.perp_dot(impulse) * inv_inertia1;
+            }
+            if let Some(w2) = w2.as_deref_mut() {
+                w2.0 += r2.perp_dot(impulse) * inv_inertia2;
+            }
+
+            writer.write(CollisionMessage {
+                a: e1,
+                b: e2,
+                normal,
+                impulse: j,
+            });
         }
     }
-}
\ No newline at end of file
+}
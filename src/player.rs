@@ -1,6 +1,7 @@
 use bevy::prelude::*;
+use rand::prelude::*;
 
-use crate::{health::*, physics::*, shooting::*, team::*};
+use crate::{game_state::*, health::*, includes::*, physics::*, scoring::*, shooting::*, team::*, topology::*};
 
 #[derive(Component)]
 pub struct Player
@@ -8,7 +9,49 @@ pub struct Player
     pub speed: f32,
 }
 
-pub fn spawn_player(commands: &mut Commands, mut meshes: ResMut<Assets<Mesh>>, mut materials: ResMut<Assets<ColorMaterial>>)
+//the person playing, it outlives every ship they fly so credit and stats carry across respawns
+#[derive(Component)]
+pub struct PlayerController;
+
+//the controller flying this ship, shots, bombs and rams are credited to it rather than the ship
+#[derive(Component, Clone, Copy)]
+pub struct ControlledBy(pub Entity);
+
+impl ControlledBy {
+    //who gets the credit for something `entity` did
+    pub fn credit(entity: Entity, controlled_by: Option<&ControlledBy>) -> Entity {
+        controlled_by.map_or(entity, |controlled_by| controlled_by.0)
+    }
+}
+
+//how the ship turns. direct spins at a fixed rate only while a key is held and overwrites the spin
+//every tick, so knocks from collisions never last past the tick they land in.
+//inertial builds up spin, coasts after release and keeps whatever spin collisions give it
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RotationHandling {
+    #[default]
+    Direct,
+    Inertial,
+}
+
+//damage is ignored while the timer runs, the ship blinks to show it
+#[derive(Component)]
+pub struct Invulnerable {
+    pub timer: f32,
+}
+
+#[derive(Component)]
+pub struct DeathExplosion {
+    pub timer: f32,
+}
+
+//counts down between the player dying and the next ship appearing
+#[derive(Resource, Default)]
+pub struct PlayerRespawn {
+    pub timer: Option<f32>,
+}
+
+pub fn spawn_player(commands: &mut Commands, mut meshes: ResMut<Assets<Mesh>>, mut materials: ResMut<Assets<ColorMaterial>>, controller: Entity, position: Vec3) -> Entity
 {
         commands.spawn((
         Player
@@ -16,15 +59,18 @@ pub fn spawn_player(commands: &mut Commands, mut meshes: ResMut<Assets<Mesh>>, m
             speed: 500.0
         },
         Velocity(Vec2::ZERO),
+        AngularVelocity(0.0),
         Sprite {
             color: Color::srgb(0.2, 0.8, 0.3),
             custom_size: Some(Vec2::new(32.0, 32.0)),
             ..default()
         },
-        Transform::default(),
-        GlobalTransform::default(),
-        Visibility::default(),
-        InheritedVisibility::default(),
+        (
+            Transform::from_translation(position),
+            GlobalTransform::default(),
+            Visibility::default(),
+            InheritedVisibility::default(),
+        ),
         Collider { radius: 15.0 },
         Mass(10.0),
         Mesh2d(meshes.add(Circle::new(15.0))),
@@ -33,8 +79,163 @@ pub fn spawn_player(commands: &mut Commands, mut meshes: ResMut<Assets<Mesh>>, m
             cooldown: 0.5,
             timer: 0.0,
             projectile_speed: 1000.0,
+            projectile_lifetime: 1.0,
+            projectile_range: 800.0,
+            damage: 2,
+            //pickups are in there so the player can knock crystals away from workers
+            projectile_layers: CollisionLayers::new(LAYER_PROJECTILE, LAYER_ENEMY | LAYER_ASTEROID | LAYER_PICKUP),
         },
-        Health(1),
+        (Health(1), MaxHealth(1)),
+        CollisionLayers::new(LAYER_PLAYER, LAYER_ALL),
+        ControlledBy(controller),
         Team::Player,
-    ));
-}
\ No newline at end of file
+    )).id()
+}
+
+pub fn setup_player(
+    mut commands: Commands,
+    meshes: ResMut<Assets<Mesh>>,
+    materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let controller = commands.spawn((PlayerController, CombatStats::default())).id();
+    spawn_player(&mut commands, meshes, materials, controller, Vec3::ZERO);
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn player_death_system(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut lives: ResMut<Lives>,
+    mut respawn: ResMut<PlayerRespawn>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut messages: MessageReader<EntityDestroyedMessage>,
+    query: Query<&Transform, With<Player>>,
+) {
+    //despawn_destroyed removes the ship itself, this only runs the death sequence
+    for message in messages.read() {
+        let Ok(transform) = query.get(message.entity) else {
+            continue;
+        };
+
+        commands.spawn((
+            DeathExplosion { timer: PLAYER_RESPAWN_DELAY },
+            Transform::from_translation(transform.translation),
+            Mesh2d(meshes.add(Annulus::new(12.0, 15.0))),
+            MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgb(1.0, 0.6, 0.1)))),
+        ));
+
+        lives.0 = lives.0.saturating_sub(1);
+
+        if lives.0 == 0 {
+            next_state.set(GameState::GameOver);
+        } else {
+            respawn.timer = Some(PLAYER_RESPAWN_DELAY);
+        }
+    }
+}
+
+pub fn death_explosion_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Transform, &mut DeathExplosion)>,
+) {
+    let dt = time.delta_secs();
+
+    for (entity, mut transform, mut explosion) in &mut query {
+        explosion.timer -= dt;
+        if explosion.timer <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        //the ring grows as the timer runs out
+        let progress = 1.0 - explosion.timer / PLAYER_RESPAWN_DELAY;
+        transform.scale = Vec3::splat(1.0 + progress * 6.0);
+    }
+}
+
+pub fn player_respawn_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    meshes: ResMut<Assets<Mesh>>,
+    materials: ResMut<Assets<ColorMaterial>>,
+    mut respawn: ResMut<PlayerRespawn>,
+    threats: Query<&Transform, With<Team>>,
+    controller: Query<Entity, With<PlayerController>>,
+) {
+    let Ok(controller) = controller.single() else {
+        return;
+    };
+
+    let Some(timer) = respawn.timer.as_mut() else {
+        return;
+    };
+
+    *timer -= time.delta_secs();
+    if *timer > 0.0 {
+        return;
+    }
+    respawn.timer = None;
+
+    let threat_positions: Vec<Vec2> = threats
+        .iter()
+        .map(|transform| transform.translation.truncate())
+        .collect();
+    let position = find_safe_spawn(&threat_positions);
+
+    let entity = spawn_player(&mut commands, meshes, materials, controller, position.extend(0.0));
+    commands.entity(entity).insert(Invulnerable {
+        timer: PLAYER_INVULNERABILITY_TIME,
+    });
+}
+
+//samples a handful of random spots and keeps the one furthest from any threat
+fn find_safe_spawn(threats: &[Vec2]) -> Vec2 {
+    let mut rng = rand::rng();
+    let half_w = WORLD_WIDTH / 2.0;
+    let half_h = WORLD_HEIGHT / 2.0;
+
+    let mut best = Vec2::ZERO;
+    let mut best_clearance = f32::MIN;
+
+    for _ in 0..16 {
+        let candidate = Vec2::new(rng.random_range(-half_w..half_w), rng.random_range(-half_h..half_h));
+        let clearance = threats
+            .iter()
+            .map(|threat| wrapped_distance(*threat, candidate))
+            .fold(f32::MAX, f32::min);
+
+        if clearance > best_clearance {
+            best_clearance = clearance;
+            best = candidate;
+        }
+    }
+
+    best
+}
+
+pub fn invulnerability_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Invulnerable, &mut Visibility)>,
+) {
+    let dt = time.delta_secs();
+
+    for (entity, mut invulnerable, mut visibility) in &mut query {
+        invulnerable.timer -= dt;
+
+        if invulnerable.timer <= 0.0 {
+            commands.entity(entity).remove::<Invulnerable>();
+            *visibility = Visibility::Inherited;
+            continue;
+        }
+
+        //blink a few times a second
+        *visibility = if (invulnerable.timer * 8.0) as i32 % 2 == 0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use rand::prelude::*;
+
+use crate::{director::*, effects::*, includes::*, warrior::*, worker::*, zone::*, MainCamera};
+
+//how far past the edge of the screen reinforcements warp in
+const REINFORCEMENT_SCREEN_MARGIN: f32 = 150.0;
+const WARP_IN_COLOR: Color = Color::srgb(0.4, 0.6, 1.0);
+
+//seconds between reinforcements, easing from `start` to `end` over the first `ramp` seconds of a zone
+#[derive(Clone, Copy)]
+pub struct SpawnCurve {
+    pub start: f32,
+    pub end: f32,
+    pub ramp: f32,
+}
+
+impl SpawnCurve {
+    pub fn interval(&self, zone_time: f32) -> f32 {
+        let progress = if self.ramp > 0.0 {
+            (zone_time / self.ramp).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+
+        self.start + (self.end - self.start) * progress
+    }
+}
+
+//time spent in the current zone and progress toward the next warp-in of each kind
+#[derive(Resource, Default)]
+pub struct Reinforcements {
+    pub zone_time: f32,
+    pub worker_timer: f32,
+    pub warrior_timer: f32,
+}
+
+//tops the zone back up to its worker and warrior counts, one ship at a time
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_reinforcements(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut reinforcements: ResMut<Reinforcements>,
+    director: Res<Director>,
+    director_settings: Res<DirectorSettings>,
+    zones: Res<Zones>,
+    current_zone: Res<CurrentZone>,
+    workers: Query<(), With<Worker>>,
+    warriors: Query<(), With<Warrior>>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+) {
+    let dt = time.delta_secs();
+    let zone = zones.get(current_zone.0);
+
+    reinforcements.zone_time += dt;
+    let zone_time = reinforcements.zone_time;
+    let interval_scale = director_settings.spawn_interval.at(director.intensity);
+
+    let worker_due = tick_reinforcement(
+        &mut reinforcements.worker_timer,
+        workers.count(),
+        zone.worker_count,
+        zone.worker_reinforcements,
+        zone_time,
+        interval_scale,
+        dt,
+    );
+    let warrior_due = tick_reinforcement(
+        &mut reinforcements.warrior_timer,
+        warriors.count(),
+        zone.warrior_count,
+        zone.warrior_reinforcements,
+        zone_time,
+        interval_scale,
+        dt,
+    );
+
+    if !worker_due && !warrior_due {
+        return;
+    }
+
+    //never on screen, so they can't be seen popping in
+    let Ok(camera_transform) = camera_query.single() else {
+        return;
+    };
+    let camera_pos = camera_transform.translation.truncate();
+    let half_screen = window_query
+        .single()
+        .map(|window| window.size() / 2.0)
+        .unwrap_or(Vec2::new(640.0, 360.0));
+
+    if worker_due {
+        let position = warp_in_position(camera_pos, half_screen);
+        spawn_worker(&mut commands, &mut meshes, &mut materials, position);
+        spawn_spark(&mut commands, &mut meshes, &mut materials, position, 20.0, 0.4, WARP_IN_COLOR);
+    }
+
+    if warrior_due {
+        let position = warp_in_position(camera_pos, half_screen);
+        spawn_warrior(&mut commands, &mut meshes, &mut materials, zone, position);
+        spawn_spark(&mut commands, &mut meshes, &mut materials, position, 20.0, 0.4, WARP_IN_COLOR);
+    }
+}
+
+//the clock only runs while the population is short, so the first replacement takes a full interval
+fn tick_reinforcement(
+    timer: &mut f32,
+    population: usize,
+    target: u32,
+    curve: SpawnCurve,
+    zone_time: f32,
+    interval_scale: f32,
+    dt: f32,
+) -> bool {
+    if population >= target as usize {
+        *timer = 0.0;
+        return false;
+    }
+
+    *timer += dt;
+    if *timer < curve.interval(zone_time) * interval_scale {
+        return false;
+    }
+
+    *timer = 0.0;
+    true
+}
+
+//a random point just outside the view, and less than half the world away so wrapping never brings it on screen
+fn warp_in_position(camera_pos: Vec2, half_screen: Vec2) -> Vec2 {
+    let mut rng = rand::rng();
+
+    let max_distance = WORLD_WIDTH.min(WORLD_HEIGHT) / 2.0;
+    let distance = (half_screen.length() + REINFORCEMENT_SCREEN_MARGIN).min(max_distance);
+    let direction = Vec2::from_angle(rng.random_range(0.0..std::f32::consts::TAU));
+
+    camera_pos + direction * distance
+}
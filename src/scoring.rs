@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+
+use crate::{health::*, includes::*, player::*};
+
+//points awarded when this entity is destroyed
+#[derive(Component)]
+pub struct ScoreValue(pub u32);
+
+//points for every point of damage that gets through, on top of any ScoreValue for finishing it off
+#[derive(Component)]
+pub struct ScorePerDamage(pub u32);
+
+//per shooter tally, kept on players and enemies alike
+#[derive(Component, Default)]
+pub struct CombatStats {
+    pub kills: u32,
+}
+
+//only kills credited to the player's controller count toward the score, enemies shooting rocks earn nothing
+pub fn award_destroy_score(
+    mut score: ResMut<GameScore>,
+    mut messages: MessageReader<EntityDestroyedMessage>,
+    values: Query<&ScoreValue>,
+    mut killers: Query<(Option<&mut CombatStats>, Has<PlayerController>)>,
+) {
+    for message in messages.read() {
+        let Some(killer) = message.killer else {
+            continue;
+        };
+
+        let Ok((stats, is_player)) = killers.get_mut(killer) else {
+            continue;
+        };
+
+        if let Some(mut stats) = stats {
+            stats.kills += 1;
+        }
+
+        if is_player && let Ok(value) = values.get(message.entity) {
+            score.0 += value.0;
+        }
+    }
+}
+
+//pays out for damage that actually got through, so armor that soaks a hit also soaks its score
+pub fn award_damage_score(
+    mut score: ResMut<GameScore>,
+    mut messages: MessageReader<DamageDealtMessage>,
+    targets: Query<&ScorePerDamage>,
+    players: Query<(), With<PlayerController>>,
+) {
+    for dealt in messages.read() {
+        let Some(source) = dealt.source else {
+            continue;
+        };
+
+        if let Ok(value) = targets.get(dealt.target)
+            && players.contains(source)
+        {
+            score.0 += value.0 * dealt.amount as u32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //runs one tick of the damage pipeline against an armored, score-per-damage target
+    fn score_after(health: i32, hits: &[(DamageKind, i32)]) -> u32 {
+        let mut app = App::new();
+        app.init_resource::<GameScore>()
+            .add_message::<DamageMessage>()
+            .add_message::<DamageDealtMessage>()
+            .add_message::<EntityDestroyedMessage>()
+            .add_systems(Update, (apply_damage, award_damage_score).chain());
+
+        let controller = app.world_mut().spawn(PlayerController).id();
+        let target = app
+            .world_mut()
+            .spawn((Transform::default(), Health(health), Armor(1000), ScorePerDamage(500)))
+            .id();
+
+        for &(kind, amount) in hits {
+            app.world_mut().write_message(DamageMessage {
+                source: Some(controller),
+                target,
+                amount,
+                kind,
+            });
+        }
+        app.update();
+
+        app.world().resource::<GameScore>().0
+    }
+
+    #[test]
+    fn shots_soaked_by_armor_score_nothing() {
+        assert_eq!(score_after(5, &[(DamageKind::Projectile, 2), (DamageKind::Collision, 3)]), 0);
+    }
+
+    #[test]
+    fn damage_through_armor_scores_per_point() {
+        assert_eq!(score_after(5, &[(DamageKind::Explosion, 2)]), 1000);
+    }
+
+    #[test]
+    fn hits_landing_together_only_score_the_health_left() {
+        assert_eq!(score_after(1, &[(DamageKind::Explosion, 1), (DamageKind::Explosion, 1)]), 500);
+    }
+}
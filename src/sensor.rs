@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use std::collections::BTreeSet;
+
+use crate::{physics::*, spatial::*, topology::*};
+
+//trigger volume that never pushes anything, it only notices colliders touching it.
+//the radius is its own, so a ship can carry a detection field far wider than its hull
+#[derive(Component)]
+pub struct OverlapSensor {
+    pub radius: f32,
+    //layers it notices, matched against the other collider's memberships
+    pub filters: u32,
+    //ordered by entity so everything reading it sees the same order every run
+    overlapping: BTreeSet<Entity>,
+}
+
+impl OverlapSensor {
+    pub fn new(radius: f32, filters: u32) -> Self {
+        OverlapSensor {
+            radius,
+            filters,
+            overlapping: BTreeSet::new(),
+        }
+    }
+
+    //everything inside as of the last sensor update
+    pub fn overlapping(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.overlapping.iter().copied()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverlapPhase {
+    Enter,
+    Stay,
+    Exit,
+}
+
+#[derive(Message)]
+pub struct OverlapMessage {
+    pub sensor: Entity,
+    pub other: Entity,
+    pub phase: OverlapPhase,
+}
+
+//compares what each sensor touches now against last tick and reports the difference
+pub fn update_sensors(
+    grid: Res<SpatialGrid>,
+    mut sensors: Query<(Entity, &Transform, &mut OverlapSensor)>,
+    layers: Query<&CollisionLayers>,
+    mut writer: MessageWriter<OverlapMessage>,
+) {
+    for (sensor_entity, transform, mut sensor) in &mut sensors {
+        let position = transform.translation.truncate();
+
+        let touching: BTreeSet<Entity> = grid
+            .query(position, sensor.radius)
+            .filter(|candidate| candidate.entity != sensor_entity)
+            .filter(|candidate| {
+                let memberships = layers.get(candidate.entity).copied().unwrap_or_default().memberships;
+                memberships & sensor.filters != 0
+            })
+            .filter(|candidate| wrapped_distance(position, candidate.position) < sensor.radius + candidate.radius)
+            .map(|candidate| candidate.entity)
+            .collect();
+
+        for &other in &touching {
+            let phase = if sensor.overlapping.contains(&other) {
+                OverlapPhase::Stay
+            } else {
+                OverlapPhase::Enter
+            };
+
+            writer.write(OverlapMessage {
+                sensor: sensor_entity,
+                other,
+                phase,
+            });
+        }
+
+        //despawned colliders drop out of the grid, so they exit too
+        for &other in sensor.overlapping.difference(&touching) {
+            writer.write(OverlapMessage {
+                sensor: sensor_entity,
+                other,
+                phase: OverlapPhase::Exit,
+            });
+        }
+
+        sensor.overlapping = touching;
+    }
+}
@@ -1,20 +1,36 @@
 use bevy::prelude::*;
 
-use crate::{asteroid::*, audio::AudioAssets, crystal::*, health::*, includes::*, physics::*, team::*};
+use crate::{audio::AudioAssets, health::*, physics::*, player::*, spatial::*, topology::*};
+
+const PROJECTILE_RADIUS: f32 = 5.0;
+const PROJECTILE_MASS: f32 = 0.2;
+//bodies at or under this mass with no health, like crystals, get shoved by shots that can reach them
+const LIGHT_BODY_MASS: f32 = 2.0;
 
 #[derive(Component)]
 pub struct Gun {
     pub cooldown: f32,
     pub timer: f32,
     pub projectile_speed: f32,
+    //shots expire at whichever of these runs out first
+    pub projectile_lifetime: f32,
+    pub projectile_range: f32,
+    pub damage: i32,
+    //what its shots can hit, including light bodies they shove aside
+    pub projectile_layers: CollisionLayers,
 }
 
 #[derive(Component)]
 pub struct Projectile {
     pub lifetime: f32,
+    //distance left before it fizzles out
+    pub range: f32,
     pub radius: f32,
     pub damage: i32,
-    pub team: Team,
+    //whoever fired it, or the controller flying it, kills and score are credited here
+    pub owner: Entity,
+    //where it was at the end of the last tick, hits are swept from here
+    pub previous_position: Vec2,
 }
 
 #[derive(Message)]
@@ -22,21 +38,30 @@ pub struct ShootMessage {
     pub entity: Entity,
 }
 
+#[derive(Message)]
+pub struct ProjectileHitMessage {
+    pub source: Entity,
+    pub target: Entity,
+    pub position: Vec2,
+    pub direction: Vec2,
+    pub damage: i32,
+}
+
 pub fn gun_system(
     mut commands: Commands,
     time: Res<Time>,
     mut messages: MessageReader<ShootMessage>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    mut query: Query<(Entity, &Transform, &mut Gun, &Team)>,
+    mut query: Query<(&Transform, &mut Gun, Option<&ControlledBy>)>,
     sounds: Res<AudioAssets>,
 ) {
-    for (_, _, mut gun, _) in &mut query {
+    for (_, mut gun, _) in &mut query {
         gun.timer -= time.delta_secs();
     }
 
     for message in messages.read() {
-        if let Ok((_, transform, mut gun, team)) = query.get_mut(message.entity) {
+        if let Ok((transform, mut gun, controlled_by)) = query.get_mut(message.entity) {
             if gun.timer > 0.0 {
                 continue;
             }
@@ -46,13 +71,23 @@ pub fn gun_system(
             commands.spawn((
                 Transform::from_translation(transform.translation),
                 Projectile {
-                    lifetime: 1.0,
-                    radius: 5.0,
-                    damage: 1,
-                    team: *team,
+                    lifetime: gun.projectile_lifetime,
+                    range: gun.projectile_range,
+                    radius: PROJECTILE_RADIUS,
+                    damage: gun.damage,
+                    owner: ControlledBy::credit(message.entity, controlled_by),
+                    previous_position: transform.translation.truncate(),
                 },
                 Velocity(velocity),
-                Mesh2d(meshes.add(Circle::new(5.0))),
+                //wrapped like everything else so shots carry on across the seam
+                WrapsAroundCamera,
+                Collider {
+                    radius: PROJECTILE_RADIUS,
+                },
+                Mass(PROJECTILE_MASS),
+                gun.projectile_layers,
+                NonSolid,
+                Mesh2d(meshes.add(Circle::new(PROJECTILE_RADIUS))),
                 MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgb(1.0, 1.0, 1.0)))),
             ));
 
@@ -63,66 +98,141 @@ pub fn gun_system(
     }
 }
 
+//offset from the shooter to where a shot fired now meets a target moving at a constant velocity.
+//falls back to the target's current position when the shot can't catch it
+pub fn lead_target(shooter: Vec2, target: Vec2, target_velocity: Vec2, projectile_speed: f32) -> Vec2 {
+    let delta = wrapped_delta(shooter, target);
+
+    //solve |delta + velocity * t| = speed * t for the earliest positive t
+    let a = target_velocity.length_squared() - projectile_speed * projectile_speed;
+    let b = 2.0 * delta.dot(target_velocity);
+    let c = delta.length_squared();
+
+    let time = if a.abs() < f32::EPSILON {
+        (b < 0.0).then(|| -c / b)
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        (discriminant >= 0.0)
+            .then(|| {
+                let root = discriminant.sqrt();
+                let t1 = (-b - root) / (2.0 * a);
+                let t2 = (-b + root) / (2.0 * a);
+                [t1, t2].into_iter().filter(|t| *t > 0.0).reduce(f32::min)
+            })
+            .flatten()
+    };
+
+    match time {
+        Some(time) => delta + target_velocity * time,
+        None => delta,
+    }
+}
+
+type ProjectileTargetFilter = (With<Health>, Without<Invulnerable>);
+type LightBodyFilter = (Without<Health>, Without<Projectile>);
+
+//what a shot ran into first along its path this tick
+enum ProjectileImpact {
+    Target(Entity),
+    LightBody(Entity),
+}
+
 pub fn projectile_system(
     mut commands: Commands,
     time: Res<Time>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut projectiles: Query<(Entity, &Transform, &mut Projectile, &Velocity)>,
-    mut targets: Query<(Entity, &Transform, &Collider, &mut Health, &Team, Option<&Asteroid>)>,
-    mut score: ResMut<GameScore>,
+    grid: Res<SpatialGrid>,
+    mut projectiles: Query<(Entity, &Transform, &mut Projectile, &Velocity, &Mass, &CollisionLayers)>,
+    targets: Query<(&Transform, &Collider, Option<&CollisionLayers>), ProjectileTargetFilter>,
+    mut light_bodies: Query<(&Transform, &Collider, &Mass, &mut Velocity, Option<&CollisionLayers>), LightBodyFilter>,
+    mut writer: MessageWriter<ProjectileHitMessage>,
 ) {
-    for (projectile_entity, proj_transform, mut projectile, proj_vel) in &mut projectiles {
-        projectile.lifetime -= time.delta_secs();
-        if projectile.lifetime <= 0.0 {
-            commands.entity(projectile_entity).despawn();
-            continue;
-        }
-
+    for (projectile_entity, proj_transform, mut projectile, proj_vel, proj_mass, proj_layers) in &mut projectiles {
+        //sweep the whole move since last tick so fast shots can't skip over small targets
+        let start = projectile.previous_position;
         let proj_pos = proj_transform.translation.truncate();
-        
-        let mut hit_something = false;
-
-        for (target_entity, target_transform, target_collider,
-            mut target_health, target_team, asteroid_opt) in &mut targets {
-            if projectile.team == Team::None || projectile.team == *target_team {
+        let motion = wrapped_delta(start, proj_pos);
+        projectile.previous_position = proj_pos;
+
+        let mut earliest: Option<(f32, ProjectileImpact)> = None;
+        for candidate in grid.query(start + motion / 2.0, motion.length() / 2.0 + projectile.radius) {
+            let (target_pos, target_radius, target_layers, impact) =
+                if let Ok((target_transform, target_collider, target_layers)) = targets.get(candidate.entity) {
+                    (
+                        target_transform.translation.truncate(),
+                        target_collider.radius,
+                        target_layers,
+                        ProjectileImpact::Target(candidate.entity),
+                    )
+                } else if let Ok((body_transform, body_collider, body_mass, _, body_layers)) =
+                    light_bodies.get(candidate.entity)
+                    && body_mass.0 <= LIGHT_BODY_MASS
+                {
+                    (
+                        body_transform.translation.truncate(),
+                        body_collider.radius,
+                        body_layers,
+                        ProjectileImpact::LightBody(candidate.entity),
+                    )
+                } else {
+                    continue;
+                };
+
+            if !proj_layers.interacts_with(&target_layers.copied().unwrap_or_default()) {
                 continue;
             }
 
-            let target_pos = target_transform.translation.truncate();
-            let dist = proj_pos.distance(target_pos);
-            let min_dist = projectile.radius + target_collider.radius;
-
-            if dist < min_dist {
-                //despawn bullet
-                commands.entity(projectile_entity).despawn();
-                hit_something = true;
-
-                //damage target
-                target_health.0 -= projectile.damage;
-
-                if asteroid_opt.is_some() {
-                    let impact_dir = proj_vel.0.normalize_or_zero() * 50.0;
-                    spawn_crystal(
-                        &mut commands,
-                        &mut meshes,
-                        &mut materials,
-                        target_transform.translation,
-                        impact_dir
-                    );
-                }
-                
-                if target_health.0 <= 0 { 
-                    commands.entity(target_entity).despawn();
-                    score.0 += 100;
-                }
+            let offset = wrapped_delta(start, target_pos);
+            let Some(t) = swept_circle_hit(motion, offset, projectile.radius + target_radius) else {
+                continue;
+            };
 
-                break; 
+            if earliest.as_ref().is_none_or(|(closest, _)| t < *closest) {
+                earliest = Some((t, impact));
             }
         }
-        
-        if hit_something {
+
+        let Some((t, impact)) = earliest else {
+            //only fizzle out once the move that used up the last of it has been checked for hits
+            projectile.lifetime -= time.delta_secs();
+            projectile.range -= motion.length();
+            if projectile.lifetime <= 0.0 || projectile.range <= 0.0 {
+                commands.entity(projectile_entity).despawn();
+            }
             continue;
+        };
+
+        //despawn bullet, everything else reacts to the message
+        commands.entity(projectile_entity).despawn();
+
+        match impact {
+            ProjectileImpact::Target(target) => {
+                writer.write(ProjectileHitMessage {
+                    source: projectile.owner,
+                    target,
+                    position: start + motion * t,
+                    direction: proj_vel.0.normalize_or_zero(),
+                    damage: projectile.damage,
+                });
+            }
+            ProjectileImpact::LightBody(body) => {
+                if let Ok((_, _, body_mass, mut body_velocity, _)) = light_bodies.get_mut(body) {
+                    body_velocity.0 += proj_vel.0 * (proj_mass.0 / body_mass.0);
+                }
+            }
         }
     }
-}
\ No newline at end of file
+}
+
+pub fn projectile_damage_system(
+    mut messages: MessageReader<ProjectileHitMessage>,
+    mut writer: MessageWriter<DamageMessage>,
+) {
+    for hit in messages.read() {
+        writer.write(DamageMessage {
+            source: Some(hit.source),
+            target: hit.target,
+            amount: hit.damage,
+            kind: DamageKind::Projectile,
+        });
+    }
+}
@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+
+use crate::{health::*, includes::*, physics::*, player::*, sinistar::*, team::*, topology::*};
+
+const SINIBOMB_RADIUS: f32 = 8.0;
+//pieces knocked off the sinistar per hit
+pub const SINIBOMB_DAMAGE: i32 = 1;
+
+#[derive(Component)]
+pub struct Sinibomb {
+    pub speed: f32,
+    pub turn_rate: f32,
+    pub lifetime: f32,
+    //credited with what it hits, the controller for player ships so it survives a respawn
+    pub owner: Entity,
+}
+
+#[derive(Message)]
+pub struct LaunchBombMessage {
+    pub entity: Entity,
+}
+
+pub fn bomb_launcher_system(
+    mut commands: Commands,
+    mut messages: MessageReader<LaunchBombMessage>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut sinibombs: ResMut<Sinibombs>,
+    query: Query<(&Transform, &Velocity, &Team, Option<&ControlledBy>)>,
+) {
+    for message in messages.read() {
+        if sinibombs.0 == 0 {
+            continue;
+        }
+
+        let Ok((transform, velocity, team, controlled_by)) = query.get(message.entity) else {
+            continue;
+        };
+
+        sinibombs.0 -= 1;
+
+        let forward = (transform.rotation * Vec3::Y).truncate();
+        let speed = 400.0;
+
+        commands.spawn((
+            Sinibomb {
+                speed,
+                turn_rate: 4.0,
+                lifetime: 6.0,
+                owner: ControlledBy::credit(message.entity, controlled_by),
+            },
+            Transform::from_translation(transform.translation).with_rotation(transform.rotation),
+            Velocity(velocity.0 + forward * speed),
+            WrapsAroundCamera,
+            //no mass, so it stays out of the physics pass but can still be shot down
+            Collider {
+                radius: SINIBOMB_RADIUS,
+            },
+            Health(1),
+            MaxHealth(1),
+            *team,
+            CollisionLayers::new(LAYER_SINIBOMB, LAYER_ALL),
+            Mesh2d(meshes.add(RegularPolygon::new(SINIBOMB_RADIUS, 4))),
+            MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgb(1.0, 0.5, 0.0)))),
+        ));
+    }
+}
+
+//steers every bomb toward the closest sinistar piece, falling back to the core
+pub fn sinibomb_homing(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bombs: Query<(Entity, &mut Transform, &mut Velocity, &mut Sinibomb)>,
+    pieces: Query<&GlobalTransform, With<SinistarPiece>>,
+    sinistars: Query<&Transform, (With<Sinistar>, Without<Sinibomb>)>,
+) {
+    let dt = time.delta_secs();
+
+    for (entity, mut transform, mut velocity, mut bomb) in &mut bombs {
+        bomb.lifetime -= dt;
+        if bomb.lifetime <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        let bomb_pos = transform.translation.truncate();
+        let closest = pieces
+            .iter()
+            .map(|piece_tf| piece_tf.translation().truncate())
+            .chain(sinistars.iter().map(|sinistar_tf| sinistar_tf.translation.truncate()))
+            .min_by(|a, b| {
+                wrapped_distance_squared(*a, bomb_pos).total_cmp(&wrapped_distance_squared(*b, bomb_pos))
+            });
+
+        let Some(target_pos) = closest else {
+            continue;
+        };
+
+        //rotate the heading toward the target, capped by the turn rate
+        let desired = wrapped_delta(bomb_pos, target_pos).normalize_or_zero();
+        let heading = velocity.0.normalize_or(desired);
+        let max_turn = bomb.turn_rate * dt;
+        let turn = heading.angle_to(desired).clamp(-max_turn, max_turn);
+        let new_heading = Vec2::from_angle(turn).rotate(heading);
+
+        velocity.0 = new_heading * bomb.speed;
+
+        let angle = new_heading.y.atan2(new_heading.x) - std::f32::consts::FRAC_PI_2;
+        transform.rotation = Quat::from_rotation_z(angle);
+    }
+}
@@ -0,0 +1,268 @@
+use bevy::prelude::*;
+
+use crate::{health::*, includes::*, navigation::*, physics::*, player::*, scoring::*, sensor::*, sinibomb::*, steering::*, team::*, topology::*, worker::*, zone::*};
+
+pub const SINISTAR_RADIUS: f32 = 40.0;
+const SINISTAR_PIECE_SIZE: f32 = 10.0;
+const SINISTAR_CONTACT_DAMAGE: i32 = 100;
+//collisions keep ships outside the body, so it reaches a little past it
+const SINISTAR_REACH: f32 = 10.0;
+//shrugs off shots and rams, only explosions get through
+const SINISTAR_ARMOR: i32 = 1000;
+const SINISTAR_PIECE_SCORE: u32 = 500;
+const SINISTAR_KILL_SCORE: u32 = 15000;
+const SINISTAR_HOME: Vec2 = Vec2::new(1500.0, 1500.0);
+
+//its health is the number of pieces it has, one knocked off per point of damage
+#[derive(Component)]
+pub struct Sinistar {
+    pub pieces_required: u32,
+}
+
+//sinistar state machine
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SinistarState {
+    #[default]
+    Building,
+    Awake,
+}
+
+#[derive(Message)]
+pub struct SinistarDestroyedMessage;
+
+//visual piece attached to the sinistar, one per delivered crystal
+#[derive(Component)]
+pub struct SinistarPiece {
+    pub slot: u32,
+}
+
+pub fn spawn_sinistar(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    zones: Res<Zones>,
+    current_zone: Res<CurrentZone>,
+    players: Query<&Transform, With<Player>>,
+) {
+    let zone = zones.get(current_zone.0);
+    let player_positions: Vec<Vec2> = players.iter().map(|transform| transform.translation.truncate()).collect();
+
+    //its usual corner, unless the player is sitting there, then as far across the world from them as it gets
+    let position = match player_positions
+        .iter()
+        .find(|player| wrapped_distance(**player, SINISTAR_HOME) < ZONE_SPAWN_CLEARANCE)
+    {
+        Some(player) => wrap_around(*player + Vec2::new(WORLD_WIDTH, WORLD_HEIGHT) / 2.0, Vec2::ZERO),
+        None => SINISTAR_HOME,
+    };
+
+    commands.spawn((
+        Sinistar {
+            pieces_required: zone.sinistar_pieces_required,
+        },
+        SinistarState::default(),
+        (
+            Health(0),
+            MaxHealth(zone.sinistar_pieces_required as i32),
+            Armor(SINISTAR_ARMOR),
+            //can be stripped of pieces while building but only dies once awake
+            Indestructible,
+            ScorePerDamage(SINISTAR_PIECE_SCORE),
+            ScoreValue(SINISTAR_KILL_SCORE),
+        ),
+        Velocity(Vec2::ZERO),
+        WrapsAroundCamera,
+        Collider {
+            radius: SINISTAR_RADIUS,
+        },
+        Mass(500.0),
+        Team::Enemy,
+        CollisionLayers::new(LAYER_ENEMY, LAYER_ALL),
+        OverlapSensor::new(SINISTAR_RADIUS + SINISTAR_REACH, LAYER_PLAYER | LAYER_ENEMY | LAYER_SINIBOMB),
+        Transform::from_translation(position.extend(1.0)),
+        Mesh2d(meshes.add(Circle::new(SINISTAR_RADIUS))),
+        MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgb(0.25, 0.2, 0.2)))),
+        (
+            Steering {
+                max_speed: 350.0,
+                max_force: zone.sinistar_acceleration,
+                damping: 0.99,
+                //the pieces are laid out around it, spinning would smear them
+                face_velocity: false,
+            },
+            Pursue {
+                weight: 1.0,
+                max_prediction: 0.5,
+                target: None,
+            },
+        ),
+        Obstacle,
+    ));
+}
+
+//workers carrying a crystal hand it over once they touch the sinistar
+pub fn sinistar_delivery(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut messages: MessageReader<OverlapMessage>,
+    mut sinistar_query: Query<(&mut Health, &MaxHealth, &mut SinistarState), With<Sinistar>>,
+    mut worker_query: Query<(&mut HasCrystal, &mut WorkerState), With<Worker>>,
+) {
+    for overlap in messages.read() {
+        if overlap.phase == OverlapPhase::Exit {
+            continue;
+        }
+
+        let Ok((mut health, max_health, mut sinistar_state)) = sinistar_query.get_mut(overlap.sensor) else {
+            continue;
+        };
+        let Ok((mut has_crystal, mut state)) = worker_query.get_mut(overlap.other) else {
+            continue;
+        };
+
+        if *state != WorkerState::Returning || !has_crystal.0 {
+            continue;
+        }
+
+        has_crystal.0 = false;
+        *state = WorkerState::Roaming;
+        commands.entity(overlap.other).remove::<NavigationTarget>();
+
+        health.0 = (health.0 + 1).min(max_health.0);
+
+        if health.0 >= max_health.0 && *sinistar_state == SinistarState::Building {
+            *sinistar_state = SinistarState::Awake;
+            commands
+                .entity(overlap.sensor)
+                .remove::<Indestructible>()
+                .insert(MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgb(0.8, 0.1, 0.1)))));
+        }
+    }
+}
+
+type ChangedSinistar<'a> = (Entity, &'a Sinistar, &'a Health, Option<&'a Children>);
+
+//keeps the piece children in step with the piece count
+pub fn sync_sinistar_pieces(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    sinistar_query: Query<ChangedSinistar, Changed<Health>>,
+    piece_query: Query<&SinistarPiece>,
+) {
+    for (sinistar_entity, sinistar, health, children) in &sinistar_query {
+        let pieces = health.0.max(0) as u32;
+        let mut existing: Vec<(Entity, u32)> = children
+            .map(|children| {
+                children
+                    .iter()
+                    .filter_map(|child| piece_query.get(child).ok().map(|piece| (child, piece.slot)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        existing.sort_by_key(|(_, slot)| *slot);
+
+        let current = existing.len() as u32;
+
+        if current > pieces {
+            for (piece_entity, _) in existing.iter().skip(pieces as usize) {
+                commands.entity(*piece_entity).despawn();
+            }
+            continue;
+        }
+
+        for slot in current..pieces {
+            //lay the pieces out in a ring around the core
+            let angle = slot as f32 / sinistar.pieces_required.max(1) as f32 * std::f32::consts::TAU;
+            let offset = Vec2::from_angle(angle) * SINISTAR_RADIUS;
+
+            let piece = commands
+                .spawn((
+                    SinistarPiece { slot },
+                    Transform::from_translation(offset.extend(0.1))
+                        .with_rotation(Quat::from_rotation_z(angle)),
+                    Mesh2d(meshes.add(RegularPolygon::new(SINISTAR_PIECE_SIZE, 4))),
+                    MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgb(0.6, 0.1, 0.1)))),
+                ))
+                .id();
+            commands.entity(sinistar_entity).add_child(piece);
+        }
+    }
+}
+
+//once awake the sinistar hunts the player, while being built it just drifts to a halt
+pub fn sinistar_ai(
+    mut sinistar_query: Query<(&SinistarState, &mut Pursue), With<Sinistar>>,
+    player_query: Query<Entity, With<Player>>,
+) {
+    for (state, mut pursue) in &mut sinistar_query {
+        pursue.target = match state {
+            SinistarState::Awake => player_query.single().ok(),
+            SinistarState::Building => None,
+        };
+    }
+}
+
+//an awake sinistar swallows the player on contact
+pub fn sinistar_attack(
+    mut messages: MessageReader<OverlapMessage>,
+    sinistar_query: Query<&SinistarState, With<Sinistar>>,
+    player_query: Query<(), With<Player>>,
+    mut writer: MessageWriter<DamageMessage>,
+) {
+    for overlap in messages.read() {
+        if overlap.phase == OverlapPhase::Exit || !player_query.contains(overlap.other) {
+            continue;
+        }
+
+        if sinistar_query.get(overlap.sensor).is_ok_and(|state| *state == SinistarState::Awake) {
+            writer.write(DamageMessage {
+                source: Some(overlap.sensor),
+                target: overlap.other,
+                amount: SINISTAR_CONTACT_DAMAGE,
+                kind: DamageKind::Collision,
+            });
+        }
+    }
+}
+
+//the shared death systems despawn and score it, this just tells the zone it's over
+pub fn sinistar_destroyed(
+    mut messages: MessageReader<EntityDestroyedMessage>,
+    sinistars: Query<(), With<Sinistar>>,
+    mut writer: MessageWriter<SinistarDestroyedMessage>,
+) {
+    for message in messages.read() {
+        if sinistars.contains(message.entity) {
+            writer.write(SinistarDestroyedMessage);
+        }
+    }
+}
+
+//a bomb reaching the sinistar blows up against it, every point of damage knocks a piece off
+pub fn sinibomb_impacts(
+    mut commands: Commands,
+    mut messages: MessageReader<OverlapMessage>,
+    sinistars: Query<(), With<Sinistar>>,
+    bombs: Query<&Sinibomb>,
+    mut writer: MessageWriter<DamageMessage>,
+) {
+    for overlap in messages.read() {
+        if overlap.phase == OverlapPhase::Exit || !sinistars.contains(overlap.sensor) {
+            continue;
+        }
+
+        let Ok(bomb) = bombs.get(overlap.other) else {
+            continue;
+        };
+
+        commands.entity(overlap.other).despawn();
+
+        writer.write(DamageMessage {
+            source: Some(bomb.owner),
+            target: overlap.sensor,
+            amount: SINIBOMB_DAMAGE,
+            kind: DamageKind::Explosion,
+        });
+    }
+}
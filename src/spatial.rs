@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+
+use crate::{includes::*, physics::*, topology::*};
+
+const CELL_SIZE: f32 = 100.0;
+
+#[derive(Clone, Copy)]
+pub struct SpatialEntry {
+    pub entity: Entity,
+    pub position: Vec2,
+    pub radius: f32,
+}
+
+//uniform grid over the wrapped world, rebuilt every frame from every collider.
+//cells on the right/top edge neighbour the ones on the left/bottom edge
+#[derive(Resource)]
+pub struct SpatialGrid {
+    cells: Vec<Vec<SpatialEntry>>,
+    columns: i32,
+    rows: i32,
+    max_radius: f32,
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        let columns = (WORLD_WIDTH / CELL_SIZE).ceil() as i32;
+        let rows = (WORLD_HEIGHT / CELL_SIZE).ceil() as i32;
+
+        SpatialGrid {
+            cells: vec![Vec::new(); (columns * rows) as usize],
+            columns,
+            rows,
+            max_radius: 0.0,
+        }
+    }
+}
+
+impl SpatialGrid {
+    pub fn clear(&mut self) {
+        for cell in &mut self.cells {
+            cell.clear();
+        }
+        self.max_radius = 0.0;
+    }
+
+    pub fn insert(&mut self, entity: Entity, position: Vec2, radius: f32) {
+        let (column, row) = self.cell_coords(position);
+        let index = self.cell_index(column, row);
+
+        self.cells[index].push(SpatialEntry {
+            entity,
+            position,
+            radius,
+        });
+        self.max_radius = self.max_radius.max(radius);
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &SpatialEntry> {
+        self.cells.iter().flatten()
+    }
+
+    //every entry whose circle overlaps the given circle, measured across the seam
+    pub fn query(&self, position: Vec2, radius: f32) -> impl Iterator<Item = &SpatialEntry> {
+        //an entry can overlap from a neighbouring cell by up to its own radius
+        let reach = radius + self.max_radius;
+        let (min_column, min_row) = self.cell_coords(position - Vec2::splat(reach));
+        let span = (2.0 * reach / CELL_SIZE).ceil() as i32 + 1;
+
+        //never visit a cell twice, even if the query is wider than the world
+        let column_span = span.min(self.columns);
+        let row_span = span.min(self.rows);
+
+        (0..row_span)
+            .flat_map(move |row_offset| {
+                (0..column_span).map(move |column_offset| {
+                    self.cell_index(min_column + column_offset, min_row + row_offset)
+                })
+            })
+            .flat_map(move |index| self.cells[index].iter())
+            .filter(move |entry| wrapped_distance(entry.position, position) <= radius + entry.radius)
+    }
+
+    fn cell_coords(&self, position: Vec2) -> (i32, i32) {
+        let x = (position.x + WORLD_WIDTH / 2.0).rem_euclid(WORLD_WIDTH);
+        let y = (position.y + WORLD_HEIGHT / 2.0).rem_euclid(WORLD_HEIGHT);
+
+        ((x / CELL_SIZE) as i32, (y / CELL_SIZE) as i32)
+    }
+
+    fn cell_index(&self, column: i32, row: i32) -> usize {
+        let column = column.rem_euclid(self.columns);
+        let row = row.rem_euclid(self.rows);
+
+        (row * self.columns + column) as usize
+    }
+}
+
+pub fn rebuild_spatial_grid(mut grid: ResMut<SpatialGrid>, query: Query<(Entity, &Transform, &Collider)>) {
+    grid.clear();
+
+    for (entity, transform, collider) in &query {
+        grid.insert(entity, transform.translation.truncate(), collider.radius);
+    }
+}
@@ -0,0 +1,381 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::{navigation::*, physics::*, spatial::*, topology::*};
+
+//damping values were tuned stepping at this rate, apply_steering rescales them to the real timestep
+const DAMPING_REFERENCE_HZ: f32 = 60.0;
+
+//movement limits for anything driven by steering behaviours.
+//each behaviour component adds its weighted force and apply_steering integrates the total
+#[derive(Component)]
+#[require(SteeringForce)]
+pub struct Steering {
+    pub max_speed: f32,
+    pub max_force: f32,
+    //fraction of the velocity kept every 1/60th of a second, whatever the tick rate
+    pub damping: f32,
+    //turn to face the direction of travel, skipped while an AimTarget is set
+    pub face_velocity: bool,
+}
+
+//sum of this tick's behaviour forces, cleared once applied
+#[derive(Component, Default)]
+pub struct SteeringForce(pub Vec2);
+
+//full speed at the NavigationTarget, which is cleared inside arrival_radius
+#[derive(Component)]
+pub struct Seek {
+    pub weight: f32,
+    pub arrival_radius: f32,
+}
+
+//like Seek but eases off inside slowing_radius instead of overshooting
+#[derive(Component)]
+pub struct Arrive {
+    pub weight: f32,
+    pub slowing_radius: f32,
+    pub arrival_radius: f32,
+}
+
+//chases where the target entity is heading rather than where it is
+#[derive(Component)]
+pub struct Pursue {
+    pub weight: f32,
+    //cap in seconds on how far ahead the target is predicted
+    pub max_prediction: f32,
+    pub target: Option<Entity>,
+}
+
+//runs straight away from where the target is right now, for as long as it's set
+#[derive(Component)]
+pub struct Flee {
+    pub weight: f32,
+    pub target: Option<Entity>,
+}
+
+//runs from where the threat is heading, only while it's inside panic_radius
+#[derive(Component)]
+pub struct Evade {
+    pub weight: f32,
+    pub panic_radius: f32,
+    pub max_prediction: f32,
+    pub threat: Option<Entity>,
+}
+
+//aimless drifting for agents with no NavigationTarget or AimTarget
+#[derive(Component)]
+pub struct Wander {
+    pub weight: f32,
+    //how far ahead the wander circle sits and how big it is
+    pub distance: f32,
+    pub radius: f32,
+    //radians per second the point on the circle can drift
+    pub jitter: f32,
+    pub angle: f32,
+}
+
+impl Wander {
+    pub fn new(weight: f32, distance: f32, radius: f32, jitter: f32) -> Self {
+        Wander {
+            weight,
+            distance,
+            radius,
+            jitter,
+            angle: 0.0,
+        }
+    }
+}
+
+//keeps clear of nearby agents that also have Separation
+#[derive(Component)]
+pub struct Separation {
+    pub weight: f32,
+    pub radius: f32,
+}
+
+//looks ahead along the velocity and steers around Obstacle colliders crossing the path
+#[derive(Component)]
+pub struct ObstacleAvoidance {
+    pub weight: f32,
+    //how far ahead to look at full speed, shorter when moving slower
+    pub look_ahead: f32,
+}
+
+//colliders that ObstacleAvoidance steers around
+#[derive(Component)]
+pub struct Obstacle;
+
+//offsets below are wrapped deltas from the agent to the point of interest
+
+pub fn seek(offset: Vec2, velocity: Vec2, max_speed: f32) -> Vec2 {
+    offset.normalize_or_zero() * max_speed - velocity
+}
+
+pub fn flee(offset: Vec2, velocity: Vec2, max_speed: f32) -> Vec2 {
+    -offset.normalize_or_zero() * max_speed - velocity
+}
+
+pub fn arrive(offset: Vec2, velocity: Vec2, max_speed: f32, slowing_radius: f32) -> Vec2 {
+    let speed = max_speed * (offset.length() / slowing_radius).min(1.0);
+    offset.normalize_or_zero() * speed - velocity
+}
+
+//where a moving target will be by the time we could get there
+fn predict(offset: Vec2, target_velocity: Vec2, max_speed: f32, max_prediction: f32) -> Vec2 {
+    let time = (offset.length() / max_speed).min(max_prediction);
+    offset + target_velocity * time
+}
+
+pub fn pursue(offset: Vec2, velocity: Vec2, target_velocity: Vec2, max_speed: f32, max_prediction: f32) -> Vec2 {
+    seek(predict(offset, target_velocity, max_speed, max_prediction), velocity, max_speed)
+}
+
+pub fn evade(offset: Vec2, velocity: Vec2, threat_velocity: Vec2, max_speed: f32, max_prediction: f32) -> Vec2 {
+    flee(predict(offset, threat_velocity, max_speed, max_prediction), velocity, max_speed)
+}
+
+type SeekingAgent<'a> = (
+    Entity,
+    &'a Transform,
+    &'a Velocity,
+    &'a Steering,
+    &'a Seek,
+    &'a NavigationTarget,
+    &'a mut SteeringForce,
+);
+
+pub fn seek_behaviour(mut commands: Commands, mut agents: Query<SeekingAgent>) {
+    for (entity, transform, velocity, steering, seek_params, target, mut force) in &mut agents {
+        let offset = wrapped_delta(transform.translation.xy(), target.0);
+
+        if offset.length() <= seek_params.arrival_radius {
+            commands.entity(entity).remove::<NavigationTarget>();
+            continue;
+        }
+
+        force.0 += seek(offset, velocity.0, steering.max_speed) * seek_params.weight;
+    }
+}
+
+type ArrivingAgent<'a> = (
+    Entity,
+    &'a Transform,
+    &'a Velocity,
+    &'a Steering,
+    &'a Arrive,
+    &'a NavigationTarget,
+    &'a mut SteeringForce,
+);
+
+pub fn arrive_behaviour(mut commands: Commands, mut agents: Query<ArrivingAgent>) {
+    for (entity, transform, velocity, steering, arrive_params, target, mut force) in &mut agents {
+        let offset = wrapped_delta(transform.translation.xy(), target.0);
+
+        if offset.length() <= arrive_params.arrival_radius {
+            commands.entity(entity).remove::<NavigationTarget>();
+            continue;
+        }
+
+        force.0 += arrive(offset, velocity.0, steering.max_speed, arrive_params.slowing_radius) * arrive_params.weight;
+    }
+}
+
+pub fn pursue_behaviour(
+    mut agents: Query<(&Transform, &Velocity, &Steering, &Pursue, &mut SteeringForce)>,
+    targets: Query<(&Transform, &Velocity)>,
+) {
+    for (transform, velocity, steering, pursue_params, mut force) in &mut agents {
+        let Some(Ok((target_transform, target_velocity))) = pursue_params.target.map(|target| targets.get(target)) else {
+            continue;
+        };
+
+        let offset = wrapped_delta(transform.translation.xy(), target_transform.translation.xy());
+        force.0 += pursue(offset, velocity.0, target_velocity.0, steering.max_speed, pursue_params.max_prediction)
+            * pursue_params.weight;
+    }
+}
+
+pub fn flee_behaviour(
+    mut agents: Query<(&Transform, &Velocity, &Steering, &Flee, &mut SteeringForce)>,
+    targets: Query<&Transform>,
+) {
+    for (transform, velocity, steering, flee_params, mut force) in &mut agents {
+        let Some(Ok(target_transform)) = flee_params.target.map(|target| targets.get(target)) else {
+            continue;
+        };
+
+        let offset = wrapped_delta(transform.translation.xy(), target_transform.translation.xy());
+        force.0 += flee(offset, velocity.0, steering.max_speed) * flee_params.weight;
+    }
+}
+
+pub fn evade_behaviour(
+    mut agents: Query<(&Transform, &Velocity, &Steering, &Evade, &mut SteeringForce)>,
+    threats: Query<(&Transform, &Velocity)>,
+) {
+    for (transform, velocity, steering, evade_params, mut force) in &mut agents {
+        let Some(Ok((threat_transform, threat_velocity))) = evade_params.threat.map(|threat| threats.get(threat)) else {
+            continue;
+        };
+
+        let offset = wrapped_delta(transform.translation.xy(), threat_transform.translation.xy());
+        if offset.length() > evade_params.panic_radius {
+            continue;
+        }
+
+        force.0 += evade(offset, velocity.0, threat_velocity.0, steering.max_speed, evade_params.max_prediction)
+            * evade_params.weight;
+    }
+}
+
+type WanderingAgent<'a> = (&'a Transform, &'a Velocity, &'a Steering, &'a mut Wander, &'a mut SteeringForce);
+
+pub fn wander_behaviour(
+    time: Res<Time>,
+    mut agents: Query<WanderingAgent, (Without<NavigationTarget>, Without<AimTarget>)>,
+) {
+    let mut rng = rand::rng();
+    let dt = time.delta_secs();
+
+    for (transform, velocity, steering, mut wander, mut force) in &mut agents {
+        let max_jitter = wander.jitter * dt;
+        wander.angle += rng.random_range(-max_jitter..=max_jitter);
+
+        //a point on a circle ahead of the agent that drifts a little every tick
+        let heading = velocity.0.try_normalize().unwrap_or((transform.rotation * Vec3::Y).xy());
+        let offset = heading * wander.distance + Vec2::from_angle(wander.angle) * wander.radius;
+
+        force.0 += seek(offset, velocity.0, steering.max_speed) * wander.weight;
+    }
+}
+
+pub fn separation_behaviour(
+    grid: Res<SpatialGrid>,
+    mut agents: Query<(Entity, &Transform, &Steering, &Separation, &mut SteeringForce)>,
+    neighbours: Query<(), With<Separation>>,
+) {
+    for (entity, transform, steering, separation, mut force) in &mut agents {
+        let position = transform.translation.xy();
+        let mut push = Vec2::ZERO;
+
+        for candidate in grid.query(position, separation.radius) {
+            if candidate.entity == entity || !neighbours.contains(candidate.entity) {
+                continue;
+            }
+
+            //the closer the neighbour the harder the push
+            let offset = wrapped_delta(position, candidate.position);
+            let distance = offset.length();
+            if distance < separation.radius {
+                push -= offset.normalize_or_zero() * (1.0 - distance / separation.radius);
+            }
+        }
+
+        force.0 += push * steering.max_force * separation.weight;
+    }
+}
+
+type AvoidingAgent<'a> = (
+    Entity,
+    &'a Transform,
+    &'a Velocity,
+    &'a Collider,
+    &'a Steering,
+    &'a ObstacleAvoidance,
+    Option<&'a NavigationTarget>,
+    &'a mut SteeringForce,
+);
+
+//a slow agent still looks this fraction of its look-ahead down its path
+const MIN_LOOK_AHEAD_FRACTION: f32 = 0.3;
+
+pub fn obstacle_avoidance_behaviour(
+    grid: Res<SpatialGrid>,
+    mut agents: Query<AvoidingAgent>,
+    obstacles: Query<&Collider, With<Obstacle>>,
+) {
+    for (entity, transform, velocity, collider, steering, avoidance, target, mut force) in &mut agents {
+        let position = transform.translation.xy();
+        let target_offset = target.map(|target| wrapped_delta(position, target.0));
+        let speed = velocity.0.length();
+
+        //look along the velocity, or toward the target when pinned against something and barely moving
+        let Some(heading) = velocity
+            .0
+            .try_normalize()
+            .filter(|_| speed >= 1.0)
+            .or_else(|| target_offset.and_then(Vec2::try_normalize))
+        else {
+            continue;
+        };
+
+        let look_ahead = avoidance.look_ahead * (speed / steering.max_speed).clamp(MIN_LOOK_AHEAD_FRACTION, 1.0);
+
+        //the closest obstacle whose collider crosses the path ahead
+        let mut nearest: Option<(f32, Vec2, f32)> = None;
+        for candidate in grid.query(position + heading * look_ahead * 0.5, look_ahead * 0.5 + collider.radius) {
+            if candidate.entity == entity {
+                continue;
+            }
+
+            let Ok(obstacle) = obstacles.get(candidate.entity) else {
+                continue;
+            };
+
+            let offset = wrapped_delta(position, candidate.position);
+            let clearance = obstacle.radius + collider.radius;
+            let along = offset.dot(heading);
+            let lateral = offset - heading * along;
+
+            if along < 0.0 || along > look_ahead + clearance || lateral.length() >= clearance {
+                continue;
+            }
+
+            //never steer away from the thing we're flying to, or from rocks beyond it
+            if let Some(target_offset) = target_offset
+                && (target_offset.distance(offset) < clearance || along > target_offset.length())
+            {
+                continue;
+            }
+
+            if nearest.is_none_or(|(closest, _, _)| along < closest) {
+                nearest = Some((along, lateral, clearance));
+            }
+        }
+
+        let Some((along, lateral, clearance)) = nearest else {
+            continue;
+        };
+
+        //go round on the side the target is on, otherwise away from the obstacle's centre
+        let side = target_offset
+            .map(|target_offset| target_offset - heading * target_offset.dot(heading))
+            .filter(|target_lateral| target_lateral.length_squared() > 1.0)
+            .unwrap_or(-lateral);
+        let away = side.try_normalize().unwrap_or(heading.perp());
+
+        //harder the closer it is, and never less than enough to slide off when pinned
+        let urgency = (1.0 - along / (look_ahead + clearance)).max(MIN_LOOK_AHEAD_FRACTION);
+        force.0 += away * steering.max_force * urgency * avoidance.weight;
+    }
+}
+
+pub fn apply_steering(
+    time: Res<Time>,
+    mut agents: Query<(&mut Transform, &mut Velocity, &Steering, &mut SteeringForce, Has<AimTarget>)>,
+) {
+    let dt = time.delta_secs();
+
+    for (mut transform, mut velocity, steering, mut force, aiming) in &mut agents {
+        velocity.0 += force.0.clamp_length_max(steering.max_force) * dt;
+        velocity.0 *= steering.damping.powf(dt * DAMPING_REFERENCE_HZ);
+        velocity.0 = velocity.0.clamp_length_max(steering.max_speed);
+        force.0 = Vec2::ZERO;
+
+        if steering.face_velocity && !aiming && velocity.length_squared() > 1.0 {
+            let angle = velocity.y.atan2(velocity.x) - std::f32::consts::FRAC_PI_2;
+            transform.rotation = Quat::from_rotation_z(angle);
+        }
+    }
+}
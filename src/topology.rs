@@ -0,0 +1,33 @@
+use bevy::prelude::*;
+
+use crate::includes::*;
+
+//the world is a torus, so two points are always at most half a world apart on each axis.
+//all sensing, collision and steering should measure through these instead of Vec2::distance
+
+//shortest vector that takes you from `from` to `to`, possibly across the seam
+pub fn wrapped_delta(from: Vec2, to: Vec2) -> Vec2 {
+    let delta = to - from;
+
+    Vec2::new(
+        wrap_axis(delta.x, WORLD_WIDTH),
+        wrap_axis(delta.y, WORLD_HEIGHT),
+    )
+}
+
+pub fn wrapped_distance(a: Vec2, b: Vec2) -> f32 {
+    wrapped_delta(a, b).length()
+}
+
+pub fn wrapped_distance_squared(a: Vec2, b: Vec2) -> f32 {
+    wrapped_delta(a, b).length_squared()
+}
+
+//folds a position back into the copy of the world centred on `origin`
+pub fn wrap_around(position: Vec2, origin: Vec2) -> Vec2 {
+    origin + wrapped_delta(origin, position)
+}
+
+fn wrap_axis(value: f32, size: f32) -> f32 {
+    (value + size / 2.0).rem_euclid(size) - size / 2.0
+}
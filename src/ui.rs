@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::includes::*;
+use crate::{game_state::*, includes::*, zone::*};
 
 #[derive(Component)]
 pub struct ScoreText;
@@ -8,6 +8,12 @@ pub struct ScoreText;
 #[derive(Component)]
 pub struct SinibombsText;
 
+#[derive(Component)]
+pub struct LivesText;
+
+#[derive(Component)]
+pub struct ZoneText;
+
 pub fn setup_score_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
     commands.spawn((
         Node {
@@ -41,24 +47,133 @@ pub fn setup_score_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
         TextColor(Color::WHITE),
         SinibombsText,
     ));
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(110.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        Text::new(format!("Lives: {}", PLAYER_STARTING_LIVES)),
+        TextFont {
+            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+            font_size: 40.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        LivesText,
+    ));
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            right: Val::Px(10.0),
+            ..default()
+        },
+        Text::new(""),
+        TextFont {
+            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+            font_size: 30.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        ZoneText,
+    ));
 }
 
-pub fn update_score_text(
-    score: Res<GameScore>,
-    sini: Res<Sinibombs>,
-    mut queries: ParamSet<(
-        Query<&mut Text, With<ScoreText>>,
-        Query<&mut Text, With<SinibombsText>>,
-    )>,
-) {
+pub fn update_score_text(score: Res<GameScore>, mut query: Query<&mut Text, With<ScoreText>>) {
     if score.is_changed() {
-        for mut text in &mut queries.p0() {
+        for mut text in &mut query {
             text.0 = format!("Score: {}", score.0);
         }
     }
+}
+
+pub fn update_sinibombs_text(sini: Res<Sinibombs>, mut query: Query<&mut Text, With<SinibombsText>>) {
     if sini.is_changed() {
-        for mut text in &mut queries.p1() {
+        for mut text in &mut query {
             text.0 = format!("Sinibombs: {}", sini.0);
         }
     }
-}
\ No newline at end of file
+}
+
+pub fn update_lives_text(lives: Res<Lives>, mut query: Query<&mut Text, With<LivesText>>) {
+    if lives.is_changed() {
+        for mut text in &mut query {
+            text.0 = format!("Lives: {}", lives.0);
+        }
+    }
+}
+
+pub fn update_zone_text(
+    zones: Res<Zones>,
+    current_zone: Res<CurrentZone>,
+    mut query: Query<&mut Text, With<ZoneText>>,
+) {
+    if current_zone.is_changed() {
+        for mut text in &mut query {
+            text.0 = format!("Zone {}: {}", current_zone.0 + 1, zones.get(current_zone.0).name);
+        }
+    }
+}
+
+fn spawn_overlay(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    heading: &str,
+    hint: &str,
+    state: GameState,
+) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(20.0),
+                ..default()
+            },
+            DespawnOnExit(state),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(heading),
+                TextFont {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 80.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.2, 0.2)),
+            ));
+            parent.spawn((
+                Text::new(hint),
+                TextFont {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 30.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+pub fn spawn_title_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    spawn_overlay(&mut commands, &asset_server, "SINIRUST", "Press Enter to start", GameState::Title);
+}
+
+pub fn spawn_pause_overlay(mut commands: Commands, asset_server: Res<AssetServer>) {
+    spawn_overlay(&mut commands, &asset_server, "PAUSED", "Esc to resume, Q to quit", GameState::Paused);
+}
+
+pub fn spawn_game_over_overlay(mut commands: Commands, asset_server: Res<AssetServer>) {
+    spawn_overlay(
+        &mut commands,
+        &asset_server,
+        "GAME OVER",
+        "Enter to play again, Esc for title",
+        GameState::GameOver,
+    );
+}
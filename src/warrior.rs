@@ -1,151 +1,270 @@
-use crate::{health::*, navigation::*, physics::*, player::*, shooting::*, team::*};
+use crate::{asteroid::*, health::*, scoring::*, navigation::*, physics::*, player::*, sensor::*, shooting::*, sinibomb::*, steering::*, team::*, topology::*, zone::*};
 use bevy::prelude::*;
 use rand::prelude::*;
 
 const WARRIOR_RADIUS: f32 = 12.0;
+const WARRIOR_TURN_RATE: f32 = 4.0;
+const WARRIOR_MINING_RANGE: f32 = 250.0;
+//half angle of the cone the aim has to be inside before the warrior pulls the trigger
+const WARRIOR_AIM_TOLERANCE: f32 = 0.15;
+//baseline values the difficulty director scales up or down
+pub const WARRIOR_DETECTION_RADIUS: f32 = 500.0;
+pub const WARRIOR_GUN_COOLDOWN: f32 = 3.0;
+pub const WARRIOR_MAX_SPEED: f32 = 170.0;
+pub const WARRIOR_MAX_FORCE: f32 = 200.0;
 
 #[derive(Component)]
 pub struct Warrior {
-    pub acceleration: f32,
-    pub detection_radius: f32,
+    //largest angle, in radians, a shot at a ship can be thrown off by
+    pub aim_error: f32,
+    //error for the next shot, rerolled every time the warrior fires
+    pub aim_offset: f32,
 }
 
 pub fn spawn_warriors(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    zones: Res<Zones>,
+    current_zone: Res<CurrentZone>,
+    players: Query<&Transform, With<Player>>,
 ) {
     let mut rng = rand::rng();
+    let zone = zones.get(current_zone.0);
+    let player_positions: Vec<Vec2> = players.iter().map(|transform| transform.translation.truncate()).collect();
 
-    for _ in 0..5 {
-        let p_x = rng.random_range(-1000.0..1000.0);
-        let p_y = rng.random_range(-1000.0..1000.0);
+    for _ in 0..zone.warrior_count {
+        let position = spawn_clear_of(&player_positions, || {
+            Vec2::new(rng.random_range(-1000.0..1000.0), rng.random_range(-1000.0..1000.0))
+        });
 
-        commands.spawn((
-            Transform::from_xyz(p_x, p_y, 0.0),
-            Warrior {
-                acceleration: 200.0,
-                detection_radius: 500.0,
+        spawn_warrior(&mut commands, &mut meshes, &mut materials, zone, position);
+    }
+}
+
+//the zone's aim error scaled by the director, clamped so a negative table entry can't make the aim roll's range invalid
+pub fn scaled_aim_error(zone_aim_error: f32, difficulty_factor: f32) -> f32 {
+    (zone_aim_error * difficulty_factor).max(0.0)
+}
+
+pub fn spawn_warrior(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    zone: &ZoneDefinition,
+    position: Vec2,
+) {
+    let mut rng = rand::rng();
+    //the director rescales this every tick, it starts at the zone's unscaled value
+    let aim_error = scaled_aim_error(zone.warrior_aim_error, 1.0);
+
+    commands.spawn((
+        Transform::from_translation(position.extend(0.0)),
+        Warrior {
+            aim_error,
+            aim_offset: rng.random_range(-aim_error..=aim_error),
+        },
+        Velocity(Vec2::ZERO),
+        Collider {
+            radius: WARRIOR_RADIUS,
+        },
+        Mass(6.0),
+        Mesh2d(meshes.add(RegularPolygon::new(WARRIOR_RADIUS, 5))),
+        MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgb(1.0, 1.0, 0.0)))),
+        Gun {
+            cooldown: WARRIOR_GUN_COOLDOWN,
+            timer: 0.0,
+            projectile_speed: 500.0,
+            projectile_lifetime: 1.2,
+            projectile_range: 600.0,
+            damage: 1,
+            projectile_layers: CollisionLayers::new(LAYER_PROJECTILE, LAYER_PLAYER | LAYER_ASTEROID | LAYER_SINIBOMB),
+        },
+        (
+            Health(2),
+            MaxHealth(2),
+            Armor(zone.warrior_armor),
+            Regeneration::new(0.5, 2.0),
+        ),
+        ScoreValue(100),
+        CombatStats::default(),
+        Team::Enemy,
+        CollisionLayers::new(LAYER_ENEMY, LAYER_ALL),
+        //picks up threats and rocks worth mining
+        OverlapSensor::new(WARRIOR_DETECTION_RADIUS, LAYER_PLAYER | LAYER_SINIBOMB | LAYER_ASTEROID),
+        (
+            Steering {
+                max_speed: WARRIOR_MAX_SPEED,
+                max_force: WARRIOR_MAX_FORCE,
+                damping: 0.98,
+                face_velocity: true,
             },
-            Velocity(Vec2::ZERO),
-            Collider {
-                radius: WARRIOR_RADIUS,
+            Seek {
+                weight: 1.0,
+                arrival_radius: WARRIOR_RADIUS,
             },
-            Mass(6.0),
-            Mesh2d(meshes.add(RegularPolygon::new(WARRIOR_RADIUS, 5))),
-            MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgb(1.0, 1.0, 0.0)))),
-            Gun {
-                cooldown: 3.0,
-                timer: 0.0,
-                projectile_speed: 500.0,
+            Pursue {
+                weight: 1.0,
+                max_prediction: 1.0,
+                target: None,
             },
-            Health(1),
-            Team::Enemy,
-        ));
-    }
+            //backs off from whatever it's fighting so it holds at gun range instead of ramming
+            Evade {
+                weight: 2.0,
+                panic_radius: 150.0,
+                max_prediction: 0.5,
+                threat: None,
+            },
+            Wander::new(0.5, 120.0, 60.0, 3.0),
+            Separation {
+                weight: 1.5,
+                radius: 60.0,
+            },
+            ObstacleAvoidance {
+                weight: 2.0,
+                look_ahead: 150.0,
+            },
+        ),
+    ));
 }
 
+type FightingWarrior<'a> = (
+    Entity,
+    &'a Transform,
+    &'a mut Warrior,
+    &'a Gun,
+    &'a mut Pursue,
+    &'a mut Evade,
+    &'a OverlapSensor,
+);
+
 pub fn warrior_ai(
     mut commands: Commands,
-    mut warriors: Query<(Entity, &Transform, &Warrior, Option<&NavigationTarget>)>,
-    players: Query<(Entity, &Transform), With<Player>>,
+    mut warriors: Query<FightingWarrior>,
+    players: Query<(Entity, &Transform, &Velocity), With<Player>>,
+    bombs: Query<(Entity, &Transform, &Velocity), With<Sinibomb>>,
+    asteroids: Query<(&Transform, &Asteroid)>,
     mut writer: MessageWriter<ShootMessage>,
 ) {
     let mut rng = rand::rng();
 
-    for (warrior_entity, warrior_transform, warrior, target) in &mut warriors {
-        update_target(
-            warrior_entity,
-            warrior,
-            warrior_transform,
-            target,
-            &mut commands,
-            players,
-            &mut writer,
-            &mut rng,
-        );
+    for (warrior_entity, warrior_transform, mut warrior, gun, mut pursue, mut evade, sensor) in &mut warriors {
+        let warrior_pos = warrior_transform.translation.xy();
+
+        let nearest = |a: &(Entity, &Transform, &Velocity), b: &(Entity, &Transform, &Velocity)| {
+            wrapped_distance_squared(a.1.translation.xy(), warrior_pos)
+                .total_cmp(&wrapped_distance_squared(b.1.translation.xy(), warrior_pos))
+        };
+
+        //incoming sinibombs are a bigger threat than the player
+        let threat = sensor
+            .overlapping()
+            .filter_map(|entity| bombs.get(entity).ok())
+            .min_by(nearest)
+            .or_else(|| sensor.overlapping().filter_map(|entity| players.get(entity).ok()).min_by(nearest))
+            .map(|(entity, transform, velocity)| (entity, transform.translation.xy(), velocity.0));
+
+        if let Some((threat_entity, threat_pos, threat_velocity)) = threat {
+            //aim where the target will be when the shot arrives, thrown off by this shot's error
+            let lead = lead_target(warrior_pos, threat_pos, threat_velocity, gun.projectile_speed);
+            let aim = Vec2::from_angle(warrior.aim_offset).rotate(lead);
+
+            pursue.target = Some(threat_entity);
+            evade.threat = Some(threat_entity);
+            commands
+                .entity(warrior_entity)
+                .insert(AimTarget(warrior_pos + aim))
+                .remove::<NavigationTarget>();
+
+            let facing = (warrior_transform.rotation * Vec3::Y).xy();
+            if gun.timer <= 0.0 && facing.angle_to(aim).abs() < WARRIOR_AIM_TOLERANCE {
+                writer.write(ShootMessage { entity: warrior_entity });
+
+                warrior.aim_offset = rng.random_range(-warrior.aim_error..=warrior.aim_error);
+            }
+
+            continue;
+        }
+
+        pursue.target = None;
+        evade.threat = None;
+
+        //nothing to fight, so break up rocks for the workers
+        let closest_asteroid = sensor
+            .overlapping()
+            .filter_map(|entity| asteroids.get(entity).ok())
+            //mined out rocks aren't worth the ammunition
+            .filter(|(_, asteroid)| !asteroid.is_depleted())
+            .map(|(asteroid_transform, _)| asteroid_transform)
+            .map(|asteroid_transform| asteroid_transform.translation.xy())
+            .min_by(|a, b| {
+                wrapped_distance_squared(*a, warrior_pos).total_cmp(&wrapped_distance_squared(*b, warrior_pos))
+            });
+
+        if let Some(asteroid_pos) = closest_asteroid {
+            mine_asteroid(warrior_entity, warrior_transform, asteroid_pos, &mut commands, &mut writer);
+
+            continue;
+        }
+
+        //nothing to do either, Wander takes over once the last target is reached
+        commands.entity(warrior_entity).remove::<AimTarget>();
     }
 }
 
-fn update_target(
+fn mine_asteroid(
     warrior_entity: Entity,
-    warrior: &Warrior,
     warrior_transform: &Transform,
-    current_target: Option<&NavigationTarget>,
+    asteroid_pos: Vec2,
     commands: &mut Commands,
-    players: Query<(Entity, &Transform), With<Player>>,
     writer: &mut MessageWriter<ShootMessage>,
-    rng: &mut ThreadRng,
 ) {
-    let mut target_transform: Option<&Transform> = None;
-    for (_, player_transform) in players {
-        let delta = player_transform.translation.xy() - warrior_transform.translation.xy();
-        let distance = delta.length();
-        if distance > warrior.detection_radius {
-            continue;
-        }
+    let delta = wrapped_delta(warrior_transform.translation.xy(), asteroid_pos);
 
-        target_transform = Some(player_transform);
-        break;
-    }
+    commands.entity(warrior_entity).insert(AimTarget(asteroid_pos));
 
-    if target_transform.is_some() {
+    //close in until the rock is in range, then hold position and shoot
+    if delta.length() > WARRIOR_MINING_RANGE {
         commands
             .entity(warrior_entity)
-            .insert(NavigationTarget(target_transform.unwrap().translation.xy()));
-
-        writer.write(ShootMessage { entity: warrior_entity });
-        
-        return;
+            .insert(NavigationTarget(asteroid_pos));
+    } else {
+        commands.entity(warrior_entity).remove::<NavigationTarget>();
     }
 
-    if current_target.is_some() {
-        return;
+    let facing = (warrior_transform.rotation * Vec3::Y).xy();
+    if facing.angle_to(delta).abs() < WARRIOR_AIM_TOLERANCE {
+        writer.write(ShootMessage { entity: warrior_entity });
     }
-
-    //pick a random point relative to current position and travel there
-    let current_pos = warrior_transform.translation.truncate();
-    let offset_x = rng.random_range(-800.0..800.0);
-    let offset_y = rng.random_range(-800.0..800.0);
-    let target = current_pos + Vec2::new(offset_x, offset_y);
-    commands
-        .entity(warrior_entity)
-        .insert(NavigationTarget(target));
 }
 
-pub fn warrior_movement(
-    mut commands: Commands,
-    time: Res<Time>,
-    mut warriors: Query<(
-        Entity,
-        &mut Transform,
-        &Warrior,
-        &mut Velocity,
-        &NavigationTarget,
-    )>,
-) {
+//turns warriors toward their aim target at a limited rate
+pub fn warrior_aiming(time: Res<Time>, mut warriors: Query<(&mut Transform, &AimTarget), With<Warrior>>) {
     let delta_time = time.delta_secs();
 
-    for (entity, mut transform, warrior, mut velocity, target) in &mut warriors {
-        //arrival check
-        let current_pos = transform.translation.xy();
-        let delta = target.0 - current_pos;
-        let distance = delta.length();
-        if distance <= WARRIOR_RADIUS {
-            commands.entity(entity).remove::<NavigationTarget>();
+    for (mut transform, aim) in &mut warriors {
+        let facing = (transform.rotation * Vec3::Y).xy();
+        let desired = wrapped_delta(transform.translation.xy(), aim.0);
+        let max_turn = WARRIOR_TURN_RATE * delta_time;
+        let turn = facing.angle_to(desired).clamp(-max_turn, max_turn);
 
-            continue;
-        }
+        transform.rotate_z(turn);
+    }
+}
 
-        // acceleration and damping
-        let direction = delta.normalize();
-        velocity.0 += warrior.acceleration * delta_time * direction;
-        velocity.0 *= 0.98;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        // rotation
-        if velocity.length_squared() > 1.0 {
-            let angle = velocity.y.atan2(velocity.x) - std::f32::consts::FRAC_PI_2;
-            transform.rotation = Quat::from_rotation_z(angle);
-        }
+    #[test]
+    fn aim_error_scales_with_the_director() {
+        assert!((scaled_aim_error(0.2, 0.5) - 0.1).abs() < 1e-6);
+        assert!((scaled_aim_error(0.2, 1.5) - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn negative_aim_error_clamps_to_zero() {
+        assert_eq!(scaled_aim_error(-0.3, 1.0), 0.0);
+        assert_eq!(scaled_aim_error(-0.3, 0.5), 0.0);
     }
 }
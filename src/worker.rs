@@ -1,7 +1,10 @@
 use bevy::prelude::*;
-use crate::{health::*, navigation::*, physics::*, team::*, crystal::*};
+use crate::{health::*, navigation::*, physics::*, player::*, team::*, crystal::*, sensor::*, sinistar::*, scoring::*, steering::*, topology::*, zone::*};
 use rand::prelude::*;
 
+//baseline the difficulty director scales up or down
+pub const WORKER_DETECTION_RADIUS: f32 = 400.0;
+
 #[derive(Component)]
 pub struct Worker;
 
@@ -15,48 +18,88 @@ pub enum WorkerState {
     Roaming,
     Collecting,
     Returning,
-}
-
-#[derive(Component)]
-pub struct WorkerStats {
-    pub speed: f32,
-    pub detection_radius: f32,
+    //carrying a crystal with the player too close, evading until it's shaken off
+    Fleeing,
 }
 
 pub fn spawn_workers(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    zones: Res<Zones>,
+    current_zone: Res<CurrentZone>,
+    players: Query<&Transform, With<Player>>,
 ) {
     let mut rng = rand::rng();
+    let zone = zones.get(current_zone.0);
+    let player_positions: Vec<Vec2> = players.iter().map(|transform| transform.translation.truncate()).collect();
 
-    for _ in 0..20 {
-        let p_x = rng.random_range(-1000.0..1000.0);
-        let p_y = rng.random_range(-1000.0..1000.0);
+    for _ in 0..zone.worker_count {
+        let position = spawn_clear_of(&player_positions, || {
+            Vec2::new(rng.random_range(-1000.0..1000.0), rng.random_range(-1000.0..1000.0))
+        });
 
-        commands.spawn((
-            Worker,
-            WorkerState::default(),
-            WorkerStats {
-                speed: 200.0,
-                detection_radius: 400.0,
-            },
-            Velocity(Vec2::ZERO),
-            Collider { radius: 12.0 },
-            Mass(5.0),
-            Mesh2d(meshes.add(RegularPolygon::new(12.0, 6))), 
-            MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgb(0.9, 0.1, 0.1)))),
-            Transform::from_xyz(p_x, p_y, 0.0),
-            Health(1),
-            Team::Enemy,
-            HasCrystal(false)
-        ));
+        spawn_worker(&mut commands, &mut meshes, &mut materials, position);
     }
 }
 
+pub fn spawn_worker(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    position: Vec2,
+) {
+    commands.spawn((
+        Worker,
+        WorkerState::default(),
+        //how far away it can spot crystals, and the player it runs from when loaded
+        OverlapSensor::new(WORKER_DETECTION_RADIUS, LAYER_PICKUP | LAYER_PLAYER),
+        Velocity(Vec2::ZERO),
+        Collider { radius: 12.0 },
+        Mass(5.0),
+        Mesh2d(meshes.add(RegularPolygon::new(12.0, 6))), 
+        MeshMaterial2d(materials.add(ColorMaterial::from(Color::srgb(0.9, 0.1, 0.1)))),
+        Transform::from_translation(position.extend(0.0)),
+        (Health(1), MaxHealth(1)),
+        ScoreValue(100),
+        Team::Enemy,
+        CollisionLayers::new(LAYER_ENEMY, LAYER_ALL),
+        HasCrystal(false),
+        (
+            Steering {
+                max_speed: 170.0,
+                max_force: 200.0,
+                damping: 0.98,
+                face_velocity: true,
+            },
+            Arrive {
+                weight: 1.0,
+                slowing_radius: 80.0,
+                arrival_radius: 20.0,
+            },
+            //used to chase crystals, which may be flying off toward the player
+            Pursue {
+                weight: 1.0,
+                max_prediction: 0.5,
+                target: None,
+            },
+            Separation {
+                weight: 1.0,
+                radius: 40.0,
+            },
+            ObstacleAvoidance {
+                weight: 2.0,
+                look_ahead: 120.0,
+            },
+        ),
+    ));
+}
+
+type IdleWorkerFilter = (With<Worker>, Without<NavigationTarget>);
+
 pub fn worker_roaming_ai(
     mut commands: Commands,
-    mut query: Query<(Entity, &Transform, &WorkerState), (With<Worker>, Without<NavigationTarget>)>,
+    mut query: Query<(Entity, &Transform, &WorkerState), IdleWorkerFilter>,
 ) {
     let mut rng = rand::rng();
 
@@ -74,80 +117,157 @@ pub fn worker_roaming_ai(
     }
 }
 
+type SensingWorker<'a> = (
+    Entity,
+    &'a Transform,
+    &'a OverlapSensor,
+    &'a mut WorkerState,
+    &'a HasCrystal,
+    &'a mut Pursue,
+);
+
 pub fn worker_sensor_ai(
     mut commands: Commands,
     // We must query for &HasCrystal to check the bool, rather than using Without<HasCrystal>
-    mut worker_query: Query<(Entity, &Transform, &WorkerStats, &mut WorkerState, &HasCrystal), With<Worker>>,
-    crystal_query: Query<&Transform, With<Crystal>>,
+    mut worker_query: Query<SensingWorker, With<Worker>>,
+    crystal_query: Query<(&Transform, &Velocity), With<Crystal>>,
+    player_query: Query<&Transform, With<Player>>,
 ) {
-    for (entity, worker_tf, stats, mut state, has_crystal) in &mut worker_query {
+    let player_positions: Vec<Vec2> = player_query.iter().map(|player_tf| player_tf.translation.truncate()).collect();
+
+    for (entity, worker_tf, sensor, mut state, has_crystal, mut pursue) in &mut worker_query {
         // Only look for crystals if we are roaming or already collecting (to update target)
         // AND we don't currently have a crystal.
         if !has_crystal.0 && matches!(*state, WorkerState::Roaming | WorkerState::Collecting) {
             
             let worker_pos = worker_tf.translation.truncate();
-            let mut closest_crystal_pos: Option<Vec2> = None;
-            
-            // The prompt requested a 200 unit detection trigger
-            let mut closest_dist = 200.0_f32.min(stats.detection_radius);
+            let mut closest_crystal: Option<(Entity, bool)> = None;
+            let mut closest_dist = f32::MAX;
+
+            //crystals on their way to the player are worth going further for, and beat any free one
+            for candidate in sensor.overlapping() {
+                let Ok((crystal_tf, crystal_velocity)) = crystal_query.get(candidate) else {
+                    continue;
+                };
 
-            for crystal_tf in &crystal_query {
                 let crystal_pos = crystal_tf.translation.truncate();
-                let dist = worker_pos.distance(crystal_pos);
+                let dist = wrapped_distance(worker_pos, crystal_pos);
+                let contested = player_positions
+                    .iter()
+                    .any(|player_pos| heading_toward(crystal_pos, crystal_velocity.0, *player_pos));
+
+                // The prompt requested a 200 unit detection trigger
+                if !contested && dist > 200.0 {
+                    continue;
+                }
 
-                if dist < closest_dist {
+                //contested first, then nearest, then lowest entity so ties never depend on iteration order
+                let closer = match closest_crystal {
+                    None => true,
+                    Some((closest_entity, closest_contested)) => {
+                        (contested && !closest_contested)
+                            || (contested == closest_contested
+                                && (dist, candidate) < (closest_dist, closest_entity))
+                    }
+                };
+
+                if closer {
                     closest_dist = dist;
-                    closest_crystal_pos = Some(crystal_pos);
+                    closest_crystal = Some((candidate, contested));
                 }
             }
 
-            if let Some(target_pos) = closest_crystal_pos {
+            if let Some((crystal_entity, _)) = closest_crystal {
                 // Determine logic: State switch
                 // If we found a crystal, we are now Collecting
                 if *state != WorkerState::Collecting {
                    *state = WorkerState::Collecting;
                 }
                 
-                // Update the moving target to the crystal's position
-                commands.entity(entity).insert(NavigationTarget(target_pos));
+                // Chase the crystal itself, it may still be moving
+                pursue.target = Some(crystal_entity);
+                commands.entity(entity).remove::<NavigationTarget>();
             } else if *state == WorkerState::Collecting {
                 // If we were collecting but can no longer see a crystal (someone else took it),
                 // go back to roaming
                 *state = WorkerState::Roaming;
-                commands.entity(entity).remove::<NavigationTarget>();
+                pursue.target = None;
             }
         }
     }
 }
 
-pub fn worker_movement(
+//a crystal drifting at the player that it will reach soon, workers try to snatch these first
+const CONTESTED_CRYSTAL_RANGE: f32 = 400.0;
+
+fn heading_toward(crystal_pos: Vec2, crystal_velocity: Vec2, player_pos: Vec2) -> bool {
+    let offset = wrapped_delta(crystal_pos, player_pos);
+
+    offset.length() < CONTESTED_CRYSTAL_RANGE
+        && crystal_velocity.length_squared() > 25.0
+        && crystal_velocity.normalize().dot(offset.normalize_or_zero()) > 0.7
+}
+
+type FleeingWorker<'a> = (
+    Entity,
+    &'a OverlapSensor,
+    &'a mut WorkerState,
+    &'a HasCrystal,
+);
+
+//loaded workers run from a player inside their sensor and go back to returning once it's left it
+pub fn worker_flee_ai(
     mut commands: Commands,
-    time: Res<Time>,
-    mut query: Query<(Entity, &mut Transform, &mut Velocity, &WorkerStats, &NavigationTarget), With<Worker>>,
+    mut worker_query: Query<FleeingWorker, With<Worker>>,
+    player_query: Query<(), With<Player>>,
 ) {
-    let dt = time.delta_secs();
+    for (entity, sensor, mut state, has_crystal) in &mut worker_query {
+        if !has_crystal.0 {
+            continue;
+        }
 
-    for (entity, mut transform, mut velocity, stats, target) in &mut query {
-        let current_pos = transform.translation.truncate();
-        let delta = target.0 - current_pos;
-        let distance_sq = delta.length_squared();
+        let player = sensor.overlapping().find(|other| player_query.contains(*other));
 
-        //arrival check
-        if distance_sq < 400.0 {
-            commands.entity(entity).remove::<NavigationTarget>();
-            velocity.0 *= 0.9; //slow until we reach the target
+        match (*state, player) {
+            (WorkerState::Returning, Some(player_entity)) => {
+                *state = WorkerState::Fleeing;
+                commands.entity(entity).remove::<NavigationTarget>().insert(Flee {
+                    weight: 2.0,
+                    target: Some(player_entity),
+                });
+            }
+            (WorkerState::Fleeing, None) => {
+                *state = WorkerState::Returning;
+                commands.entity(entity).remove::<Flee>();
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn worker_return_ai(
+    mut commands: Commands,
+    mut worker_query: Query<(Entity, &Transform, &WorkerState, &mut Pursue), With<Worker>>,
+    sinistar_query: Query<&Transform, With<Sinistar>>,
+) {
+    for (entity, worker_tf, state, mut pursue) in &mut worker_query {
+        if *state != WorkerState::Returning {
             continue;
         }
 
-        //movement logic
-        let direction = delta.normalize();
-        velocity.0 += direction * stats.speed * dt;
-        velocity.0 *= 0.98;
+        //head for the closest sinistar, it may be drifting so refresh every frame
+        let worker_pos = worker_tf.translation.truncate();
+        let closest = sinistar_query
+            .iter()
+            .map(|sinistar_tf| sinistar_tf.translation.truncate())
+            .min_by(|a, b| {
+                wrapped_distance_squared(*a, worker_pos).total_cmp(&wrapped_distance_squared(*b, worker_pos))
+            });
+
+        pursue.target = None;
 
-        //visual rotation
-        if velocity.length_squared() > 1.0 {
-            let angle = velocity.y.atan2(velocity.x) - std::f32::consts::FRAC_PI_2;
-            transform.rotation = Quat::from_rotation_z(angle);
+        if let Some(target_pos) = closest {
+            commands.entity(entity).insert(NavigationTarget(target_pos));
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,227 @@
+use bevy::prelude::*;
+use rand::prelude::*;
+
+use crate::{asteroid::*, physics::*, player::*, reinforcement::*, sinistar::*, topology::*, warrior::*, worker::*};
+
+//nothing in a freshly built zone appears closer than this to the player
+pub const ZONE_SPAWN_CLEARANCE: f32 = 400.0;
+
+//everything that changes from one zone to the next
+#[derive(Clone)]
+pub struct ZoneDefinition {
+    pub name: &'static str,
+    pub asteroid_count: u32,
+    //relative weights, they don't need to add up to one
+    pub asteroid_mix: Vec<(AsteroidArchetype, f32)>,
+    //population the reinforcement spawner keeps topping back up to
+    pub worker_count: u32,
+    pub warrior_count: u32,
+    pub worker_reinforcements: SpawnCurve,
+    pub warrior_reinforcements: SpawnCurve,
+    //flat damage reduction, player shots deal 2 so armor 1 means two hits
+    pub warrior_armor: i32,
+    //radians a warrior's shot can stray from a perfect lead, lower is deadlier
+    pub warrior_aim_error: f32,
+    //fewer pieces means the sinistar is finished sooner
+    pub sinistar_pieces_required: u32,
+    pub sinistar_acceleration: f32,
+}
+
+//the zone table, designers add zones here and the spawn functions pick them up
+#[derive(Resource)]
+pub struct Zones(pub Vec<ZoneDefinition>);
+
+impl Default for Zones {
+    fn default() -> Self {
+        Zones(vec![
+            ZoneDefinition {
+                name: "Worker Zone",
+                asteroid_count: 100,
+                asteroid_mix: vec![
+                    (AsteroidArchetype::Rock, 0.6),
+                    (AsteroidArchetype::SmallRock, 0.25),
+                    (AsteroidArchetype::IronRock, 0.1),
+                    (AsteroidArchetype::CrystalPlanetoid, 0.05),
+                ],
+                worker_count: 20,
+                warrior_count: 5,
+                worker_reinforcements: SpawnCurve {
+                    start: 6.0,
+                    end: 3.0,
+                    ramp: 120.0,
+                },
+                warrior_reinforcements: SpawnCurve {
+                    start: 20.0,
+                    end: 10.0,
+                    ramp: 180.0,
+                },
+                warrior_armor: 0,
+                warrior_aim_error: 0.25,
+                sinistar_pieces_required: 20,
+                sinistar_acceleration: 150.0,
+            },
+            ZoneDefinition {
+                name: "Warrior Zone",
+                asteroid_count: 80,
+                asteroid_mix: vec![
+                    (AsteroidArchetype::Rock, 0.5),
+                    (AsteroidArchetype::SmallRock, 0.2),
+                    (AsteroidArchetype::IronRock, 0.2),
+                    (AsteroidArchetype::ExplosiveRock, 0.1),
+                ],
+                worker_count: 15,
+                warrior_count: 12,
+                worker_reinforcements: SpawnCurve {
+                    start: 6.0,
+                    end: 3.0,
+                    ramp: 120.0,
+                },
+                warrior_reinforcements: SpawnCurve {
+                    start: 12.0,
+                    end: 5.0,
+                    ramp: 120.0,
+                },
+                warrior_armor: 0,
+                warrior_aim_error: 0.18,
+                sinistar_pieces_required: 18,
+                sinistar_acceleration: 180.0,
+            },
+            ZoneDefinition {
+                name: "Planetoid Zone",
+                asteroid_count: 140,
+                asteroid_mix: vec![
+                    (AsteroidArchetype::Rock, 0.3),
+                    (AsteroidArchetype::CrystalPlanetoid, 0.3),
+                    (AsteroidArchetype::SmallRock, 0.2),
+                    (AsteroidArchetype::IronRock, 0.1),
+                    (AsteroidArchetype::ExplosiveRock, 0.1),
+                ],
+                worker_count: 25,
+                warrior_count: 8,
+                worker_reinforcements: SpawnCurve {
+                    start: 5.0,
+                    end: 2.5,
+                    ramp: 120.0,
+                },
+                warrior_reinforcements: SpawnCurve {
+                    start: 15.0,
+                    end: 8.0,
+                    ramp: 150.0,
+                },
+                warrior_armor: 0,
+                warrior_aim_error: 0.12,
+                sinistar_pieces_required: 15,
+                sinistar_acceleration: 200.0,
+            },
+            ZoneDefinition {
+                name: "Void Zone",
+                asteroid_count: 50,
+                asteroid_mix: vec![
+                    (AsteroidArchetype::Rock, 0.3),
+                    (AsteroidArchetype::SmallRock, 0.3),
+                    (AsteroidArchetype::IronRock, 0.2),
+                    (AsteroidArchetype::ExplosiveRock, 0.2),
+                ],
+                worker_count: 30,
+                warrior_count: 15,
+                worker_reinforcements: SpawnCurve {
+                    start: 4.0,
+                    end: 2.0,
+                    ramp: 90.0,
+                },
+                warrior_reinforcements: SpawnCurve {
+                    start: 10.0,
+                    end: 4.0,
+                    ramp: 90.0,
+                },
+                warrior_armor: 1,
+                warrior_aim_error: 0.06,
+                sinistar_pieces_required: 12,
+                sinistar_acceleration: 240.0,
+            },
+        ])
+    }
+}
+
+impl Zones {
+    //past the end of the table the last zone repeats
+    pub fn get(&self, index: usize) -> &ZoneDefinition {
+        self.0
+            .get(index.min(self.0.len().saturating_sub(1)))
+            .expect("the zone table is empty, add at least one ZoneDefinition")
+    }
+}
+
+//checked once at startup so a broken table fails with a clear message instead of mid-game
+pub fn validate_zones(zones: Res<Zones>) {
+    assert!(!zones.0.is_empty(), "the zone table is empty, add at least one ZoneDefinition");
+}
+
+//rerolls `pick` until it lands clear of every player, keeping the last try if none does
+pub fn spawn_clear_of(players: &[Vec2], mut pick: impl FnMut() -> Vec2) -> Vec2 {
+    let mut position = pick();
+
+    for _ in 0..16 {
+        if players
+            .iter()
+            .all(|player| wrapped_distance(*player, position) >= ZONE_SPAWN_CLEARANCE)
+        {
+            break;
+        }
+        position = pick();
+    }
+
+    position
+}
+
+impl ZoneDefinition {
+    //weighted pick from the zone's asteroid mix, plain rock if the mix is empty
+    pub fn pick_asteroid(&self, rng: &mut impl Rng) -> AsteroidArchetype {
+        let total: f32 = self.asteroid_mix.iter().map(|(_, weight)| weight).sum();
+        if total <= 0.0 {
+            return AsteroidArchetype::Rock;
+        }
+
+        let mut roll = rng.random_range(0.0..total);
+        for (archetype, weight) in &self.asteroid_mix {
+            if roll < *weight {
+                return *archetype;
+            }
+            roll -= weight;
+        }
+
+        AsteroidArchetype::Rock
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct CurrentZone(pub usize);
+
+//when the sinistar dies the field is cleared and the next zone is built, keeping clear of the player
+pub fn advance_zone(
+    mut commands: Commands,
+    mut messages: MessageReader<SinistarDestroyedMessage>,
+    mut current_zone: ResMut<CurrentZone>,
+    mut reinforcements: ResMut<Reinforcements>,
+) {
+    if messages.read().count() == 0 {
+        return;
+    }
+
+    current_zone.0 += 1;
+    *reinforcements = Reinforcements::default();
+
+    commands.run_system_cached(clear_zone);
+    commands.run_system_cached(spawn_asteroids);
+    commands.run_system_cached(spawn_workers);
+    commands.run_system_cached(spawn_warriors);
+    commands.run_system_cached(spawn_sinistar);
+}
+
+type ZoneEntityFilter = (With<Velocity>, Without<Player>, Without<ChildOf>);
+
+fn clear_zone(mut commands: Commands, query: Query<Entity, ZoneEntityFilter>) {
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+}